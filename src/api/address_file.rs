@@ -0,0 +1,347 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::base::{currency::Currency, token::Token};
+
+use super::{edit_request::NewAddress, payment_data::check_fields};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AddressFileError {
+    #[error("Cannot read address file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unsupported address file format `{0}`, expected csv, json or yaml")]
+    UnknownFormat(String),
+    #[error("Cannot parse address file: {0}")]
+    Parse(String),
+    #[error("Address file contains no addresses")]
+    Empty,
+    #[error("Address file has invalid rows:\n{}", format_rows(.0))]
+    InvalidRows(Vec<RowError>),
+}
+
+/// Validation error of a single row. Rows are numbered from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RowError {
+    pub row: usize,
+    pub reason: String,
+}
+
+impl Display for RowError {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "Row {}: {}", self.row, self.reason)
+    }
+}
+
+pub(super) fn format_rows(rows: &[RowError]) -> String {
+    rows.iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,
+    Json,
+    Yaml,
+}
+
+impl TryFrom<&Path> for FileFormat {
+    type Error = AddressFileError;
+
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_ref() {
+            "csv" => Ok(FileFormat::Csv),
+            "json" => Ok(FileFormat::Json),
+            "yaml" | "yml" => Ok(FileFormat::Yaml),
+            _ => Err(AddressFileError::UnknownFormat(extension)),
+        }
+    }
+}
+
+/// An address as written in a file. Also used by the manifest
+#[derive(Deserialize, Debug)]
+pub(super) struct AddressRow {
+    currency: Option<String>, // Not needed for tokens
+    token: Option<String>,
+    network: Option<String>,  // Settlement network of the token
+    contract: Option<String>, // Contract address of the token
+    address: String,
+    description: Option<String>,
+    default: Option<bool>,
+}
+
+/// Reads addresses from a CSV, JSON or YAML file. The format is chosen by the file extension.
+pub fn read_address_file(path: &Path) -> Result<Vec<NewAddress>, AddressFileError> {
+    let format = FileFormat::try_from(path)?;
+    let content = std::fs::read_to_string(path)?;
+    parse_addresses(format, &content)
+}
+
+/// Parses and validates all rows. Fails, if any of the rows is invalid.
+pub fn parse_addresses(
+    format: FileFormat,
+    content: &str,
+) -> Result<Vec<NewAddress>, AddressFileError> {
+    let rows = match format {
+        FileFormat::Csv => csv::Reader::from_reader(content.as_bytes())
+            .deserialize::<AddressRow>()
+            .map(|row| row.map_err(|err| err.to_string()))
+            .collect(),
+        FileFormat::Json => parse_rows::<serde_json::Value, _>(
+            serde_json::from_str(content),
+            serde_json::from_value,
+        )?,
+        FileFormat::Yaml => parse_rows::<serde_yaml::Value, _>(
+            serde_yaml::from_str(content),
+            serde_yaml::from_value,
+        )?,
+    };
+    validate_rows(rows)
+}
+
+/// Parses the document as a list first, so that malformed entries can be reported by row
+fn parse_rows<V, E: Display>(
+    values: Result<Vec<V>, E>,
+    from_value: fn(V) -> Result<AddressRow, E>,
+) -> Result<Vec<Result<AddressRow, String>>, AddressFileError> {
+    let values = values.map_err(|err| AddressFileError::Parse(err.to_string()))?;
+    Ok(values
+        .into_iter()
+        .map(|value| from_value(value).map_err(|err| err.to_string()))
+        .collect())
+}
+
+fn validate_rows(
+    rows: Vec<Result<AddressRow, String>>,
+) -> Result<Vec<NewAddress>, AddressFileError> {
+    if rows.is_empty() {
+        return Err(AddressFileError::Empty);
+    }
+
+    let mut addresses = vec![];
+    let mut errors = vec![];
+    for (i, row) in rows.into_iter().enumerate() {
+        match row.and_then(validate_row) {
+            Ok(address) => addresses.push(address),
+            Err(reason) => errors.push(RowError { row: i + 1, reason }),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(AddressFileError::InvalidRows(errors));
+    }
+    Ok(addresses)
+}
+
+pub(super) fn validate_row(row: AddressRow) -> Result<NewAddress, String> {
+    let (currency, token) = validate_category(&row)?;
+    let address = row.address.trim().to_owned();
+    let description = row
+        .description
+        .map(|d| d.trim().to_owned())
+        .filter(|d| !d.is_empty());
+    check_fields(&address, description.as_deref()).map_err(|err| err.to_string())?;
+    Ok(NewAddress {
+        currency,
+        token,
+        address,
+        description,
+        default: row.default.unwrap_or_default(),
+    })
+}
+
+fn validate_category(row: &AddressRow) -> Result<(Currency, Option<Token>), String> {
+    let non_empty = |v: &Option<String>| {
+        v.as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_owned)
+    };
+    match (non_empty(&row.currency), non_empty(&row.token)) {
+        (Some(_), Some(_)) => Err("Either currency or token must be given, not both".to_owned()),
+        (Some(currency), None) => {
+            let currency = Currency::try_from(currency.as_str()).map_err(|err| err.to_string())?;
+            if currency.is_token() {
+                return Err("Token addresses require a token and a network".to_owned());
+            }
+            Ok((currency, None))
+        }
+        (None, Some(token)) => {
+            let network = non_empty(&row.network).ok_or("Token requires a network")?;
+            let contract = non_empty(&row.contract);
+            let token =
+                Token::new(&token, &network, contract.as_deref()).map_err(|err| err.to_string())?;
+            Ok((Token::currency(), Some(token)))
+        }
+        (None, None) => Err("Currency is missing".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cardano(address: &str, description: Option<&str>) -> NewAddress {
+        NewAddress {
+            currency: Currency::try_from("Cardano").unwrap(),
+            token: None,
+            address: address.to_owned(),
+            description: description.map(|d| d.to_owned()),
+            default: false,
+        }
+    }
+
+    fn row_errors(result: Result<Vec<NewAddress>, AddressFileError>) -> Vec<RowError> {
+        match result {
+            Err(AddressFileError::InvalidRows(errors)) => errors,
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_detects_format_by_extension() {
+        assert_eq!(
+            FileFormat::try_from(Path::new("a.CSV")).unwrap(),
+            FileFormat::Csv
+        );
+        assert_eq!(
+            FileFormat::try_from(Path::new("a.yml")).unwrap(),
+            FileFormat::Yaml
+        );
+        assert!(matches!(
+            FileFormat::try_from(Path::new("a.txt")),
+            Err(AddressFileError::UnknownFormat(ext)) if ext == "txt"
+        ));
+    }
+
+    #[test]
+    fn it_parses_csv() {
+        let content = "currency,address,description\nCardano,ADDR1,\n0x1012,ADDR2,Savings\n";
+        assert_eq!(
+            parse_addresses(FileFormat::Csv, content).unwrap(),
+            vec![cardano("ADDR1", None), cardano("ADDR2", Some("Savings"))]
+        );
+    }
+
+    #[test]
+    fn it_parses_json() {
+        let content = r#"[
+            { "currency": "Cardano", "address": "ADDR1" },
+            { "currency": "Cardano", "address": "ADDR2", "description": "Savings" }
+        ]"#;
+        assert_eq!(
+            parse_addresses(FileFormat::Json, content).unwrap(),
+            vec![cardano("ADDR1", None), cardano("ADDR2", Some("Savings"))]
+        );
+    }
+
+    #[test]
+    fn it_parses_yaml() {
+        let content = "- currency: Cardano\n  address: ADDR1\n- currency: Cardano\n  address: ADDR2\n  description: Savings\n";
+        assert_eq!(
+            parse_addresses(FileFormat::Yaml, content).unwrap(),
+            vec![cardano("ADDR1", None), cardano("ADDR2", Some("Savings"))]
+        );
+    }
+
+    #[test]
+    fn it_parses_default_column() {
+        let content =
+            "currency,address,description,default\nCardano,ADDR1,,true\nCardano,ADDR2,,\n";
+        let addresses = parse_addresses(FileFormat::Csv, content).unwrap();
+        assert_eq!(
+            addresses.iter().map(|a| a.default).collect::<Vec<_>>(),
+            vec![true, false]
+        );
+    }
+
+    #[test]
+    fn it_parses_token_rows() {
+        let content = r#"[{ "token": "usdc", "network": "ethereum", "address": "0xADDR" }]"#;
+        assert_eq!(
+            parse_addresses(FileFormat::Json, content).unwrap(),
+            vec![NewAddress {
+                currency: Token::currency(),
+                token: Some(Token::new("USDC", "Ethereum", None).unwrap()),
+                address: "0xADDR".to_owned(),
+                description: None,
+                default: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_token_rows() {
+        let content = r#"[
+            { "token": "USDC", "address": "0xADDR" },
+            { "currency": "Cardano", "token": "USDC", "network": "Ethereum", "address": "X" },
+            { "currency": "CryptoToken", "address": "0xADDR" },
+            { "currency": "0x6300", "network": "Ethereum", "address": "0xADDR" }
+        ]"#;
+        assert_eq!(
+            row_errors(parse_addresses(FileFormat::Json, content)),
+            vec![
+                RowError {
+                    row: 1,
+                    reason: "Token requires a network".to_owned()
+                },
+                RowError {
+                    row: 2,
+                    reason: "Either currency or token must be given, not both".to_owned()
+                },
+                RowError {
+                    row: 3,
+                    reason: "Token addresses require a token and a network".to_owned()
+                },
+                RowError {
+                    row: 4,
+                    reason: "Token addresses require a token and a network".to_owned()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn it_fails_on_empty_file() {
+        assert!(matches!(
+            parse_addresses(FileFormat::Json, "[]"),
+            Err(AddressFileError::Empty)
+        ));
+    }
+
+    #[test]
+    fn it_reports_every_invalid_row() {
+        let content = r#"[
+            { "currency": "Cardano", "address": "ADDR1" },
+            { "currency": "UNKNOWN", "address": "ADDR2" },
+            { "currency": "Cardano", "address": " " },
+            { "currency": "Cardano" }
+        ]"#;
+        let rows: Vec<usize> = row_errors(parse_addresses(FileFormat::Json, content))
+            .into_iter()
+            .map(|e| e.row)
+            .collect();
+        assert_eq!(rows, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn it_reports_invalid_csv_rows() {
+        let content = "currency,address,description\nCardano,ADDR1|X,\n";
+        assert_eq!(
+            row_errors(parse_addresses(FileFormat::Csv, content)),
+            vec![RowError {
+                row: 1,
+                reason: "Address must not contain `|`".to_owned()
+            }]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fs, io::Write, path::Path};
+
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+use super::error::ClientError;
+
+/// Tokens obtained by logging in with email and password
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub access_token: String,
+    pub refresh_token: String,
+    // Account, which logged in. Missing in sessions stored by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub requires_2fa: Option<String>, // Second factor method, if the account has it enabled
+}
+
+impl TryFrom<TokenResponse> for Session {
+    type Error = ClientError;
+
+    fn try_from(response: TokenResponse) -> Result<Self, Self::Error> {
+        if response.requires_2fa.is_some() {
+            return Err(ClientError::TwoFactorRequired);
+        }
+        Ok(Session {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            email: None,
+        })
+    }
+}
+
+/// How requests are authenticated
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuthStrategy {
+    /// Static `x-api-key` header
+    ApiKey(String),
+    /// JWT access token, which can be refreshed on 401
+    Bearer(Session),
+    /// No credentials. Only login is possible
+    Anonymous,
+}
+
+impl AuthStrategy {
+    /// API key takes precedence over a stored session
+    pub fn from_settings(settings: &Settings) -> Result<Self, ClientError> {
+        if let Some(api_key) = &settings.yat_api_key {
+            return Ok(AuthStrategy::ApiKey(api_key.clone()));
+        }
+        match &settings.session_file {
+            Some(path) => Ok(load_session(path)?
+                .map(AuthStrategy::Bearer)
+                .unwrap_or(AuthStrategy::Anonymous)),
+            None => Ok(AuthStrategy::Anonymous),
+        }
+    }
+
+    /// Who requests are made for. Keeps cached lookups of different accounts apart
+    pub fn identity(&self) -> Option<String> {
+        match self {
+            AuthStrategy::ApiKey(api_key) => Some(format!("key:{}", api_key)),
+            AuthStrategy::Bearer(session) => Some(format!(
+                "session:{}",
+                session.email.as_deref().unwrap_or(&session.refresh_token)
+            )),
+            AuthStrategy::Anonymous => None,
+        }
+    }
+
+    pub fn header(&self) -> Result<(HeaderName, HeaderValue), ClientError> {
+        match self {
+            AuthStrategy::ApiKey(api_key) => Ok((
+                HeaderName::from_static("x-api-key"),
+                HeaderValue::from_str(api_key).map_err(|_| ClientError::InvalidApiKey)?,
+            )),
+            AuthStrategy::Bearer(session) => Ok((
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", session.access_token))
+                    .map_err(|_| ClientError::InvalidApiKey)?,
+            )),
+            AuthStrategy::Anonymous => Err(ClientError::NotAuthenticated),
+        }
+    }
+}
+
+pub fn load_session(path: &Path) -> Result<Option<Session>, ClientError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(session_error)?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(session_error)
+}
+
+/// Stores the session in a file, readable only by the current user
+pub fn save_session(path: &Path, session: &Session) -> Result<(), ClientError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(session_error)?;
+    }
+    let content = serde_json::to_string_pretty(session).map_err(session_error)?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(session_error)?;
+    // The mode above applies to new files only
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(session_error)?;
+    }
+    file.write_all(content.as_bytes()).map_err(session_error)
+}
+
+fn session_error(err: impl std::fmt::Display) -> ClientError {
+    ClientError::SessionError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        Session {
+            access_token: "ACCESS".to_owned(),
+            refresh_token: "REFRESH".to_owned(),
+            email: None,
+        }
+    }
+
+    #[test]
+    fn it_saves_and_loads_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("session.json");
+
+        save_session(&path, &session()).unwrap();
+
+        assert_eq!(load_session(&path).unwrap(), Some(session()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn it_loads_nothing_if_session_file_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            load_session(&dir.path().join("session.json")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn it_builds_headers_for_each_strategy() {
+        let (name, value) = AuthStrategy::ApiKey("KEY".to_owned()).header().unwrap();
+        assert_eq!(
+            (name.as_str(), value.to_str().unwrap()),
+            ("x-api-key", "KEY")
+        );
+
+        let (name, value) = AuthStrategy::Bearer(session()).header().unwrap();
+        assert_eq!(
+            (name.as_str(), value.to_str().unwrap()),
+            ("authorization", "Bearer ACCESS")
+        );
+
+        assert!(matches!(
+            AuthStrategy::Anonymous.header(),
+            Err(ClientError::NotAuthenticated)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_sessions_requiring_second_factor() {
+        let response = TokenResponse {
+            access_token: "ACCESS".to_owned(),
+            refresh_token: "REFRESH".to_owned(),
+            requires_2fa: Some("GoogleAuthenticator".to_owned()),
+        };
+        assert!(matches!(
+            Session::try_from(response),
+            Err(ClientError::TwoFactorRequired)
+        ));
+    }
+}
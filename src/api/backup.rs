@@ -0,0 +1,262 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cache::unix_time,
+    edit_request::{EditRequest, InsertData},
+    record::Record,
+};
+
+/// Version of the backup file format
+pub const BACKUP_VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Cannot read backup: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Cannot parse backup: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Unsupported backup version `{0}`, expected {BACKUP_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// A record as exported. The data is restored as it is, the description and default flag of
+/// payment addresses are there for the reader only
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupRecord {
+    pub tag: String,
+    pub data: String,
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default: bool,
+}
+
+impl From<&Record> for BackupRecord {
+    fn from(record: &Record) -> Self {
+        let payment_data = record.currency().map(|_| record.payment_data());
+        Self {
+            tag: record.tag.clone(),
+            data: record.data.clone(),
+            hash: record.hash.clone(),
+            description: payment_data.as_ref().and_then(|d| d.description.clone()),
+            default: payment_data.is_some_and(|d| d.default),
+        }
+    }
+}
+
+impl From<&BackupRecord> for Record {
+    fn from(record: &BackupRecord) -> Self {
+        Self {
+            tag: record.tag.clone(),
+            data: record.data.clone(),
+            hash: record.hash.clone(),
+        }
+    }
+}
+
+/// Snapshot of all records of a yat
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Backup {
+    pub version: u32,
+    pub yat: String,
+    pub api_url: String,
+    pub exported_at: u64, // Seconds since UNIX epoch
+    pub records: Vec<BackupRecord>,
+}
+
+impl Backup {
+    pub fn new(yat: &str, api_url: &str, records: &[Record]) -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            yat: yat.to_owned(),
+            api_url: api_url.to_owned(),
+            exported_at: unix_time(),
+            records: records.iter().map(BackupRecord::from).collect(),
+        }
+    }
+
+    /// Reads a backup written by `yat export`
+    pub fn read(path: &Path) -> Result<Self, BackupError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// Pretty printed JSON, as written by `yat export`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Backups can be serialized")
+    }
+
+    pub fn parse(content: &str) -> Result<Self, BackupError> {
+        let backup: Self = serde_json::from_str(content)?;
+        if backup.version != BACKUP_VERSION {
+            return Err(BackupError::UnsupportedVersion(backup.version));
+        }
+        Ok(backup)
+    }
+}
+
+/// Records to delete and to insert, so that a yat gets back to a backup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestorePlan {
+    pub yat: String,
+    pub delete: Vec<Record>,
+    pub insert: Vec<BackupRecord>,
+}
+
+impl RestorePlan {
+    pub fn is_empty(&self) -> bool {
+        self.delete.is_empty() && self.insert.is_empty()
+    }
+
+    /// The single edit request restoring the backup
+    pub fn edit_request(&self) -> EditRequest {
+        EditRequest {
+            insert: self
+                .insert
+                .iter()
+                .map(|record| InsertData {
+                    data: record.data.clone(),
+                    tag: record.tag.clone(),
+                })
+                .collect(),
+            delete: self.delete.iter().map(|r| r.hash.clone()).collect(),
+        }
+    }
+}
+
+impl Display for RestorePlan {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}:", self.yat)?;
+        if self.is_empty() {
+            return write!(fmt, "\n  No changes");
+        }
+        for record in &self.delete {
+            write!(fmt, "\n  - {}", record)?;
+        }
+        for record in &self.insert {
+            write!(fmt, "\n  + {}", Record::from(record))?;
+        }
+        write!(
+            fmt,
+            "\nRestore: {} to add, {} to destroy.",
+            self.insert.len(),
+            self.delete.len()
+        )
+    }
+}
+
+fn same_record(live: &Record, backup: &BackupRecord) -> bool {
+    live.tag.eq_ignore_ascii_case(&backup.tag) && live.data == backup.data
+}
+
+/// Live records missing from the backup are deleted, backed up records missing from the yat
+/// are inserted. Records present in both are kept, whatever their hash.
+pub fn plan_restore(backup: &Backup, records: &[Record]) -> RestorePlan {
+    let mut matched = vec![false; records.len()];
+    let mut insert = vec![];
+    for backup_record in &backup.records {
+        let found =
+            (0..records.len()).find(|&i| !matched[i] && same_record(&records[i], backup_record));
+        match found {
+            Some(i) => matched[i] = true,
+            None => insert.push(backup_record.clone()),
+        }
+    }
+    let delete = records
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(record, _)| record.clone())
+        .collect();
+    RestorePlan {
+        yat: backup.yat.clone(),
+        delete,
+        insert,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(tag: &str, data: &str, hash: &str) -> Record {
+        Record {
+            tag: tag.to_owned(),
+            data: data.to_owned(),
+            hash: hash.to_owned(),
+        }
+    }
+
+    #[test]
+    fn it_exports_payment_details() {
+        let backup = Backup::new(
+            "🐱🐶",
+            "https://a.y.at",
+            &[
+                record("0x1012", "ADDR1|Savings||true", "H1"),
+                record("0x4001", "https://example.com", "H2"),
+            ],
+        );
+        assert_eq!(backup.version, BACKUP_VERSION);
+        assert_eq!(backup.records[0].description.as_deref(), Some("Savings"));
+        assert!(backup.records[0].default);
+        assert_eq!(backup.records[1].description, None);
+        assert!(!backup.records[1].default);
+
+        assert_eq!(Backup::parse(&backup.to_json()).unwrap(), backup);
+    }
+
+    #[test]
+    fn it_rejects_unknown_version() {
+        let content =
+            r#"{"version": 2, "yat": "🐱🐶", "api_url": "", "exported_at": 0, "records": []}"#;
+        assert!(matches!(
+            Backup::parse(content),
+            Err(BackupError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn it_plans_restore() {
+        let backup = Backup::new(
+            "🐱🐶",
+            "https://a.y.at",
+            &[
+                record("0x1012", "ADDR1|||true", "H1"),
+                record("0x4001", "https://example.com", "H2"),
+            ],
+        );
+        let live = vec![
+            record("0x1012", "ADDR1|||true", "H5"),
+            record("0x1012", "ADDR2", "H6"),
+        ];
+        let plan = plan_restore(&backup, &live);
+
+        assert_eq!(
+            plan.to_string(),
+            "🐱🐶:\n  - Cardano: ADDR2\n  + Website redirect: https://example.com\n\
+             Restore: 1 to add, 1 to destroy."
+        );
+        assert_eq!(
+            plan.edit_request(),
+            EditRequest {
+                insert: vec![InsertData {
+                    data: "https://example.com".to_owned(),
+                    tag: "0x4001".to_owned(),
+                }],
+                delete: vec!["H6".to_owned()],
+            }
+        );
+        assert!(plan_restore(
+            &backup,
+            &backup.records.iter().map(Record::from).collect::<Vec<_>>()
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+use crate::base::yat::Yat;
+
+use super::{error::ClientError, payment_address::PaymentAddressResponse};
+
+/// Lookup response of a yat together with the validators given by the server
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub api_url: String, // API, which returned the response
+    pub stored_at: u64,  // Seconds since UNIX epoch
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub response: PaymentAddressResponse,
+}
+
+impl CacheEntry {
+    pub fn new(
+        api_url: &str,
+        response: PaymentAddressResponse,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> Self {
+        Self {
+            api_url: api_url.to_owned(),
+            stored_at: unix_time(),
+            etag,
+            last_modified,
+            response,
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_time().saturating_sub(self.stored_at))
+    }
+
+    /// Marks the entry as fresh, after the server has confirmed it did not change
+    pub fn touch(&mut self) {
+        self.stored_at = unix_time();
+    }
+}
+
+/// Lookup responses stored as one JSON file per yat. Entries of other APIs and accounts are
+/// kept apart, so that switching environments never serves stale lookups of another one.
+pub struct ResponseCache {
+    dir: PathBuf,
+    api_url: String,
+    scope: String, // Fingerprint of the API URL and account, prefixes the file names
+}
+
+impl ResponseCache {
+    /// The identity names the API key or session, see `AuthStrategy::identity`
+    pub fn new(dir: PathBuf, api_url: &str, identity: Option<&str>) -> Self {
+        let identity = format!("{}\n{}", api_url, identity.unwrap_or_default());
+        let scope = hex::encode(&digest(&SHA256, identity.as_bytes()).as_ref()[..8]);
+        Self {
+            dir,
+            api_url: api_url.to_owned(),
+            scope,
+        }
+    }
+
+    /// Missing or unreadable entries, and entries of another API, are treated as not cached
+    pub fn load(&self, yat: &Yat) -> Option<CacheEntry> {
+        let content = fs::read_to_string(self.path(yat)).ok()?;
+        serde_json::from_str::<CacheEntry>(&content)
+            .ok()
+            .filter(|entry| entry.api_url == self.api_url)
+    }
+
+    pub fn store(&self, yat: &Yat, entry: &CacheEntry) -> Result<(), ClientError> {
+        fs::create_dir_all(&self.dir).map_err(cache_error)?;
+        let content = serde_json::to_string(entry).map_err(cache_error)?;
+        // Concurrent readers never see a half written entry
+        let path = self.path(yat);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content).map_err(cache_error)?;
+        fs::rename(&tmp_path, &path).map_err(cache_error)
+    }
+
+    pub fn remove(&self, yat: &Yat) -> Result<(), ClientError> {
+        match fs::remove_file(self.path(yat)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(cache_error(err)),
+            _ => Ok(()),
+        }
+    }
+
+    // Emoji are hex encoded to keep file names portable
+    fn path(&self, yat: &Yat) -> PathBuf {
+        let name: String = yat.as_ref().bytes().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}-{}.json", self.scope, name))
+    }
+}
+
+/// Human readable age, e.g. `3h 5m`
+pub fn describe_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+pub(super) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn cache_error(err: impl std::fmt::Display) -> ClientError {
+    ClientError::CacheError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::settings::Settings;
+
+    use super::*;
+
+    fn yat() -> Yat {
+        let settings = Settings {
+            api_url: "https://".to_owned(),
+            yat_api_key: None,
+            session_file: None,
+            required_yat_length: 1..=5,
+            retry: Default::default(),
+            http: Default::default(),
+            cache: Default::default(),
+        };
+        Yat::new(&settings, "💪💪").unwrap()
+    }
+
+    fn entry() -> CacheEntry {
+        CacheEntry::new(
+            "https://a.y.at",
+            PaymentAddressResponse {
+                error: None,
+                result: Some(Default::default()),
+                status: true,
+            },
+            Some("\"v1\"".to_owned()),
+            None,
+        )
+    }
+
+    #[test]
+    fn it_stores_loads_and_removes_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().join("yat"), "https://a.y.at", Some("KEY"));
+        let entry = entry();
+
+        assert_eq!(cache.load(&yat()), None);
+        cache.store(&yat(), &entry).unwrap();
+        assert_eq!(cache.load(&yat()), Some(entry));
+        cache.remove(&yat()).unwrap();
+        assert_eq!(cache.load(&yat()), None);
+        assert!(cache.remove(&yat()).is_ok());
+    }
+
+    #[test]
+    fn it_treats_corrupt_entries_as_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_owned(), "https://a.y.at", None);
+        fs::write(cache.path(&yat()), "{").unwrap();
+
+        assert_eq!(cache.load(&yat()), None);
+    }
+
+    #[test]
+    fn it_keeps_apis_and_keys_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_owned(), "https://a.y.at", Some("KEY"));
+        cache.store(&yat(), &entry()).unwrap();
+
+        let other_key = ResponseCache::new(dir.path().to_owned(), "https://a.y.at", Some("KEY2"));
+        assert_eq!(other_key.load(&yat()), None);
+        let other_api = ResponseCache::new(dir.path().to_owned(), "http://127.0.0.1", Some("KEY"));
+        assert_eq!(other_api.load(&yat()), None);
+
+        // An entry of another API is a miss, even if it is found under the same name
+        let entry = CacheEntry {
+            api_url: "http://127.0.0.1".to_owned(),
+            ..entry()
+        };
+        cache.store(&yat(), &entry).unwrap();
+        assert_eq!(cache.load(&yat()), None);
+    }
+
+    #[test]
+    fn it_describes_age() {
+        assert_eq!(describe_age(Duration::from_secs(42)), "42s");
+        assert_eq!(describe_age(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(describe_age(Duration::from_secs(7500)), "2h 5m");
+        assert_eq!(describe_age(Duration::from_secs(90000)), "1d 1h");
+    }
+}
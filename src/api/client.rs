@@ -1,95 +1,723 @@
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT};
+use std::{sync::Mutex, time::Duration};
 
-use crate::{base::yat::Yat, cli::CreateAddress, settings::Settings};
+use futures::{stream, StreamExt};
+
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ACCEPT, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+    Method, StatusCode,
+};
+
+use crate::{
+    base::{currency::Currency, tag::Tag, token::Token, yat::Yat},
+    settings::{FixtureMode, Settings},
+};
 
 use super::{
-    edit_request::EditRequest,
+    auth::{save_session, AuthStrategy, LoginRequest, RefreshRequest, Session, TokenResponse},
+    backup::{plan_restore, Backup, RestorePlan},
+    cache::{CacheEntry, ResponseCache},
+    edit_request::{
+        AddressSelector, AddressUpdate, EditPreview, EditRequest, InsertData, NewAddress, NewRecord,
+    },
     error::ClientError,
-    payment_address::{ApiError, FriendlyPaymentAddress, PaymentAddressResponse},
+    fixtures::Fixtures,
+    http,
+    manifest::{plan_addresses, YatPlan},
+    owned_yat::{OwnedYat, OwnedYatsResponse, OWNED_YATS_PAGE_SIZE},
+    payment_address::{
+        ApiErrorBody, FriendlyPaymentAddress, PaymentAddressResponse, PaymentAddresses, YatLookup,
+    },
+    payment_data::{check_fields, PaymentData},
+    record::{find_payment_records, find_records, Record, RecordsResponse},
+    retry,
+    search::{SearchResponse, YatSearch},
 };
 
 /// Remote API interface
 pub struct ApiClient<'a> {
     settings: &'a Settings,
+    client: reqwest::Client,
+    auth: Mutex<AuthStrategy>,
+    cache: Option<ResponseCache>,
+    fixtures: Option<Fixtures>,
 }
 
 impl<'a> ApiClient<'a> {
-    pub fn new(settings: &'a Settings) -> Self {
-        Self { settings }
+    pub fn new(settings: &'a Settings) -> Result<Self, ClientError> {
+        let client = http::build_client(&settings.http)?;
+        let auth = AuthStrategy::from_settings(settings)?;
+        let identity = auth.identity();
+        Ok(Self {
+            settings,
+            client,
+            auth: Mutex::new(auth),
+            cache: settings
+                .cache
+                .dir
+                .clone()
+                .map(|dir| ResponseCache::new(dir, &settings.api_url, identity.as_deref())),
+            fixtures: settings
+                .http
+                .fixtures
+                .as_ref()
+                .map(|mode| Fixtures::load(mode, &settings.api_url))
+                .transpose()?,
+        })
     }
 
     fn full_uri(&self, path: &str) -> String {
         format!("{}{}", self.settings.api_url, path)
     }
 
-    fn headers(&self) -> Result<HeaderMap, ClientError> {
-        let api_key = HeaderValue::from_str(&self.settings.yat_api_key)
-            .map_err(|_| ClientError::InvalidApiKey)?;
-
+    // Authentication headers are added by `send`
+    fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-        headers.insert(HeaderName::from_static("x-api-key"), api_key);
-        Ok(headers)
+        headers
+    }
+
+    /// Log in with email and password. The session is stored for later runs
+    pub async fn login(&self, email: String, password: String) -> Result<(), ClientError> {
+        let uri = self.full_uri("/auth/token");
+        let request = self
+            .client
+            .post(uri)
+            .headers(self.headers())
+            .json(&LoginRequest {
+                email: email.clone(),
+                password,
+            });
+        let response = self
+            .dispatch(request)
+            .await??
+            .assert_success()
+            .await?
+            .json::<TokenResponse>()
+            .await?;
+        self.store_session(Session {
+            email: Some(email),
+            ..Session::try_from(response)?
+        })
+    }
+
+    /// Obtain a new access token, if the client uses a session.
+    /// Returns false, if there is nothing to refresh.
+    async fn refresh_session(&self) -> Result<bool, ClientError> {
+        let (refresh_token, email) = match &*self.auth.lock().expect("Auth lock is poisoned") {
+            AuthStrategy::Bearer(session) => (session.refresh_token.clone(), session.email.clone()),
+            _ => return Ok(false),
+        };
+        log::debug!("Access token has expired, refreshing");
+
+        let uri = self.full_uri("/auth/token/refresh");
+        let request = self
+            .client
+            .post(uri)
+            .headers(self.headers())
+            .json(&RefreshRequest { refresh_token });
+        let response = self
+            .dispatch(request)
+            .await??
+            .assert_success()
+            .await?
+            .json::<TokenResponse>()
+            .await?;
+        self.store_session(Session {
+            email,
+            ..Session::try_from(response)?
+        })?;
+        Ok(true)
+    }
+
+    // Replayed sessions are redacted, so they are kept for the current run only
+    fn store_session(&self, session: Session) -> Result<(), ClientError> {
+        let replaying = matches!(self.settings.http.fixtures, Some(FixtureMode::Replay(_)));
+        if let (Some(path), false) = (&self.settings.session_file, replaying) {
+            save_session(path, &session)?;
+        }
+        *self.auth.lock().expect("Auth lock is poisoned") = AuthStrategy::Bearer(session);
+        Ok(())
     }
 
-    /// Fetch payment addresses
+    /// Fetch payment addresses. Fails, if any of the addresses has an unknown category.
     pub async fn fetch_payment_addresses(
         &self,
         yat: &Yat,
     ) -> Result<Vec<FriendlyPaymentAddress>, ClientError> {
+        Ok(self.fetch_payment_addresses_lenient(yat).await?.strict()?)
+    }
+
+    /// Fetch payment addresses, keeping the ones of unknown categories aside.
+    /// Cached responses are served within TTL and revalidated with the API afterwards.
+    pub async fn fetch_payment_addresses_lenient(
+        &self,
+        yat: &Yat,
+    ) -> Result<PaymentAddresses, ClientError> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.load(yat));
+        match &cached {
+            Some(entry) if self.settings.cache.offline || entry.age() < self.settings.cache.ttl => {
+                return into_payment_addresses(entry.response.clone(), Some(entry.age()));
+            }
+            None if self.settings.cache.offline => return Err(ClientError::NotCached),
+            _ => {}
+        }
+
         let uri = self.full_uri(&format!("/emoji_id/{}/payment", yat.as_ref()));
-        let response = reqwest::Client::new()
+        let mut request = self.client.get(uri).headers(self.headers());
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = self.send(request, true).await?;
+        if let (StatusCode::NOT_MODIFIED, Some(mut entry)) = (response.status(), cached) {
+            entry.touch();
+            self.store_cached(yat, &entry);
+            return into_payment_addresses(entry.response, None);
+        }
+
+        let response = response.assert_success().await?;
+        let etag = header_value(&response, ETAG);
+        let last_modified = header_value(&response, LAST_MODIFIED);
+        let body = response.json::<PaymentAddressResponse>().await?;
+        if body.error.is_none() {
+            self.store_cached(
+                yat,
+                &CacheEntry::new(&self.settings.api_url, body.clone(), etag, last_modified),
+            );
+        }
+        into_payment_addresses(body, None)
+    }
+
+    // Caching is best effort, a failure does not fail the lookup
+    fn store_cached(&self, yat: &Yat, entry: &CacheEntry) {
+        if let Some(cache) = &self.cache {
+            if let Err(err) = cache.store(yat, entry) {
+                log::warn!("{}", err);
+            }
+        }
+    }
+
+    fn remove_cached(&self, yat: &Yat) {
+        if let Some(cache) = &self.cache {
+            if let Err(err) = cache.remove(yat) {
+                log::warn!("{}", err);
+            }
+        }
+    }
+
+    /// Fetch payment addresses of many yats, running at most `concurrency` lookups at once.
+    /// Every yat gets its own result in the given order, so one failure doesn't abort the batch.
+    pub async fn fetch_many<I>(&self, yats: I, concurrency: usize) -> Vec<YatLookup>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        stream::iter(yats)
+            .map(|yat| async move {
+                let result = match Yat::new(self.settings, &yat) {
+                    Ok(valid) => self.fetch_payment_addresses_lenient(&valid).await,
+                    Err(err) => Err(err.into()),
+                };
+                YatLookup { yat, result }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// List yats owned by the authenticated account, going through all pages
+    pub async fn list_owned_yats(&self) -> Result<Vec<OwnedYat>, ClientError> {
+        let mut yats = vec![];
+        for page in 1.. {
+            let uri = self.full_uri("/emoji_id");
+            let request = self
+                .client
+                .get(uri)
+                .query(&[("page", page), ("limit", OWNED_YATS_PAGE_SIZE)])
+                .headers(self.headers());
+            let response = self
+                .send(request, true)
+                .await?
+                .assert_success()
+                .await?
+                .json::<OwnedYatsResponse>()
+                .await?;
+            if let Some(error) = response.error {
+                return Err(ClientError::from_api_error(None, error));
+            }
+
+            let page_yats = response.result.unwrap_or_default();
+            // Without paging info a short page is the last one
+            let last_page = match &response.paging {
+                Some(paging) => paging.is_last_page(),
+                None => page_yats.len() < OWNED_YATS_PAGE_SIZE,
+            };
+            let empty = page_yats.is_empty();
+            yats.extend(page_yats);
+            if last_page || empty {
+                break;
+            }
+        }
+        Ok(yats)
+    }
+
+    /// Check availability and price of a yat, which is not bought yet
+    pub async fn search(&self, yat: &Yat) -> Result<YatSearch, ClientError> {
+        let uri = self.full_uri("/emoji_id/search");
+        let request = self
+            .client
             .get(uri)
-            .headers(self.headers()?)
-            .send()
+            .query(&[("emoji_id", yat.as_ref())])
+            .headers(self.headers());
+        let response = self
+            .send_public(request)
+            .await?
+            .assert_success()
             .await?
-            .assert_success()?
-            .json::<PaymentAddressResponse>()
+            .json::<SearchResponse>()
             .await?;
-        if let Some(ApiError { code, reason }) = response.error {
-            return Err(ClientError::ApiResponseError { code, reason });
+        match (response.error, response.result) {
+            (Some(error), _) => Err(ClientError::from_api_error(None, error)),
+            (None, Some(result)) => Ok(YatSearch {
+                result,
+                alternates: response.alternates,
+            }),
+            (None, None) => Err(ClientError::YatNotFound),
         }
+    }
 
-        response
-            .result
-            .unwrap_or_default()
-            .into_values()
-            .map(|a| FriendlyPaymentAddress::try_from(a).map_err(|err| err.into()))
-            .collect()
+    /// Fetch all records of a yat (including their hashes)
+    pub async fn fetch_records(&self, yat: &Yat) -> Result<Vec<Record>, ClientError> {
+        let uri = self.full_uri(&format!("/emoji_id/{}", yat.as_ref()));
+        let request = self.client.get(uri).headers(self.headers());
+        let response = self
+            .send(request, true)
+            .await?
+            .assert_success()
+            .await?
+            .json::<RecordsResponse>()
+            .await?;
+        if let Some(error) = response.error {
+            return Err(ClientError::from_api_error(None, error));
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// Snapshot of all records of a yat
+    pub async fn export(&self, yat: &Yat) -> Result<Backup, ClientError> {
+        let records = self.fetch_records(yat).await?;
+        Ok(Backup::new(yat.as_ref(), &self.settings.api_url, &records))
+    }
+
+    /// Changes, which bring the records of the yat back to the backup
+    pub async fn plan_restore(&self, backup: &Backup) -> Result<RestorePlan, ClientError> {
+        let yat = Yat::new(self.settings, &backup.yat)?;
+        let records = self.fetch_records(&yat).await?;
+        Ok(plan_restore(backup, &records))
+    }
+
+    /// Changes, which make the payment addresses of a yat match the desired ones
+    pub async fn plan_yat(
+        &self,
+        yat: &Yat,
+        desired: &[NewAddress],
+    ) -> Result<YatPlan, ClientError> {
+        let records = self.fetch_records(yat).await?;
+        Ok(plan_addresses(yat.as_ref(), &records, desired))
+    }
+
+    /// Edit request, which creates payment addresses.
+    /// New default addresses take the default flag from the existing ones.
+    pub async fn plan_create_payment_addresses(
+        &self,
+        yat: &Yat,
+        new_addresses: Vec<NewAddress>,
+    ) -> Result<EditRequest, ClientError> {
+        for address in &new_addresses {
+            check_fields(&address.address, address.description.as_deref())?;
+        }
+        let mut defaults: Vec<(&Currency, Option<&Token>)> = vec![];
+        for address in new_addresses.iter().filter(|a| a.default) {
+            let category = (&address.currency, address.token.as_ref());
+            if !defaults.contains(&category) {
+                defaults.push(category);
+            }
+        }
+        let records = match defaults.is_empty() {
+            true => vec![],
+            false => self.fetch_records(yat).await?,
+        };
+        let mut edit_request = EditRequest::delete(vec![]);
+        for (currency, token) in defaults {
+            edit_request.extend(EditRequest::set_default(&records, currency, token, None));
+        }
+        edit_request.extend(new_addresses.into());
+        Ok(edit_request)
     }
 
-    /// Create a payment address
-    pub async fn create_payment_address(
+    /// Edit request, which makes the selected payment address the default one of its currency.
+    /// It is empty, if the address is the default one already.
+    pub async fn plan_set_default_address(
         &self,
         yat: &Yat,
-        create_address: CreateAddress,
+        selector: AddressSelector,
+    ) -> Result<EditRequest, ClientError> {
+        let records = self.fetch_records(yat).await?;
+        let found =
+            find_payment_records(&records, &selector.currency, selector.address.as_deref())?;
+        let token = found[0].payment_data().token;
+        let edit_request = EditRequest::set_default(
+            &records,
+            &selector.currency,
+            token.as_ref(),
+            Some(&found[0].hash),
+        );
+        Ok(edit_request)
+    }
+
+    /// Edit request, which deletes payment addresses matching the given currency (and address)
+    pub async fn plan_delete_payment_address(
+        &self,
+        yat: &Yat,
+        selector: AddressSelector,
+    ) -> Result<EditRequest, ClientError> {
+        let records = self.fetch_records(yat).await?;
+        let hashes =
+            find_payment_records(&records, &selector.currency, selector.address.as_deref())?
+                .into_iter()
+                .map(|r| r.hash.clone())
+                .collect();
+
+        Ok(EditRequest::delete(hashes))
+    }
+
+    /// Edit request, which replaces a payment address (or its description),
+    /// so that the old and the new address never coexist
+    pub async fn plan_replace_payment_address(
+        &self,
+        yat: &Yat,
+        update: AddressUpdate,
+    ) -> Result<EditRequest, ClientError> {
+        let selector = &update.selector;
+        let records = self.fetch_records(yat).await?;
+        let found =
+            find_payment_records(&records, &selector.currency, selector.address.as_deref())?;
+        let old_data = found[0].payment_data();
+        check_fields(
+            update.new_address.as_deref().unwrap_or(&old_data.address),
+            update.description.as_deref(),
+        )?;
+        // The signature proves ownership of the old address only
+        let signature = match &update.new_address {
+            Some(new_address) if *new_address != old_data.address => None,
+            _ => old_data.signature,
+        };
+        let data = PaymentData {
+            token: old_data.token,
+            address: update.new_address.unwrap_or(old_data.address),
+            description: update.description.or(old_data.description),
+            signature,
+            default: old_data.default,
+        };
+
+        let edit_request = EditRequest {
+            insert: vec![InsertData {
+                data: data.to_string(),
+                tag: update.selector.currency.get_hex_code(),
+            }],
+            delete: found.into_iter().map(|r| r.hash.clone()).collect(),
+        };
+        Ok(edit_request)
+    }
+
+    /// Edit request, which deletes records of a tag.
+    /// The data is required, if the tag has several records
+    pub async fn plan_remove_records(
+        &self,
+        yat: &Yat,
+        tag: &Tag,
+        data: Option<&str>,
+    ) -> Result<EditRequest, ClientError> {
+        let records = self.fetch_records(yat).await?;
+        let hashes = find_records(&records, tag, data)?
+            .into_iter()
+            .map(|r| r.hash.clone())
+            .collect();
+
+        Ok(EditRequest::delete(hashes))
+    }
+
+    /// Edit request, which makes the record the only one of its tag, e.g. a website redirect
+    pub async fn plan_set_record(
+        &self,
+        yat: &Yat,
+        record: NewRecord,
+    ) -> Result<EditRequest, ClientError> {
+        let records = self.fetch_records(yat).await?;
+        let mut edit_request = EditRequest::delete(
+            records
+                .iter()
+                .filter(|r| r.tag().as_ref() == Ok(&record.tag))
+                .map(|r| r.hash.clone())
+                .collect(),
+        );
+        edit_request.insert.push(record.into());
+        Ok(edit_request)
+    }
+
+    /// Create payment addresses within a single edit request
+    pub async fn create_payment_addresses(
+        &self,
+        yat: &Yat,
+        new_addresses: Vec<NewAddress>,
+    ) -> Result<(), ClientError> {
+        let edit_request = self
+            .plan_create_payment_addresses(yat, new_addresses)
+            .await?;
+        self.apply_edit(yat, &edit_request).await
+    }
+
+    /// Make the selected payment address the default one of its currency
+    pub async fn set_default_address(
+        &self,
+        yat: &Yat,
+        selector: AddressSelector,
+    ) -> Result<(), ClientError> {
+        let edit_request = self.plan_set_default_address(yat, selector).await?;
+        self.apply_edit(yat, &edit_request).await
+    }
+
+    /// Delete payment addresses matching the given currency (and address)
+    pub async fn delete_payment_address(
+        &self,
+        yat: &Yat,
+        selector: AddressSelector,
+    ) -> Result<(), ClientError> {
+        let edit_request = self.plan_delete_payment_address(yat, selector).await?;
+        self.apply_edit(yat, &edit_request).await
+    }
+
+    /// Replace a payment address (or its description) within a single edit request
+    pub async fn replace_payment_address(
+        &self,
+        yat: &Yat,
+        update: AddressUpdate,
+    ) -> Result<(), ClientError> {
+        let edit_request = self.plan_replace_payment_address(yat, update).await?;
+        self.apply_edit(yat, &edit_request).await
+    }
+
+    /// Add records of any tag within a single edit request
+    pub async fn add_records(&self, yat: &Yat, records: Vec<NewRecord>) -> Result<(), ClientError> {
+        self.apply_edit(yat, &records.into()).await
+    }
+
+    /// Delete records of a tag. The data is required, if the tag has several records
+    pub async fn remove_records(
+        &self,
+        yat: &Yat,
+        tag: &Tag,
+        data: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let edit_request = self.plan_remove_records(yat, tag, data).await?;
+        self.apply_edit(yat, &edit_request).await
+    }
+
+    /// Make the record the only one of its tag, e.g. a website redirect
+    pub async fn set_record(&self, yat: &Yat, record: NewRecord) -> Result<(), ClientError> {
+        let edit_request = self.plan_set_record(yat, record).await?;
+        self.apply_edit(yat, &edit_request).await
+    }
+
+    /// Request, which `apply_edit` would send. Credentials are redacted
+    pub fn preview_edit<'e>(&self, yat: &Yat, edit_request: &'e EditRequest) -> EditPreview<'e> {
+        let auth_header = self
+            .auth
+            .lock()
+            .expect("Auth lock is poisoned")
+            .header()
+            .ok()
+            .map(|(name, _)| name.to_string());
+        EditPreview {
+            method: Method::PATCH,
+            url: self.full_uri(&format!("/emoji_id/{}", yat.as_ref())),
+            auth_header,
+            body: edit_request,
+        }
+    }
+
+    /// Send an edit request for a yat. An empty request is not sent
+    pub async fn apply_edit(
+        &self,
+        yat: &Yat,
+        edit_request: &EditRequest,
     ) -> Result<(), ClientError> {
+        if edit_request.is_empty() {
+            return Ok(());
+        }
         let uri = self.full_uri(&format!("/emoji_id/{}", yat.as_ref()));
 
-        // We care only for status code
-        let _ = reqwest::Client::new()
+        let request = self
+            .client
             .patch(uri)
-            .json::<EditRequest>(&create_address.into())
-            .headers(self.headers()?)
-            .send()
+            .json::<EditRequest>(edit_request)
+            .headers(self.headers());
+
+        // We care only for status code
+        let _ = self
+            .send(request, self.settings.retry.retry_patch)
             .await?
-            .assert_success()?;
+            .assert_success()
+            .await?;
 
+        self.remove_cached(yat);
         Ok(())
     }
+
+    /// Send an authenticated request. An expired session is refreshed once.
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        retryable: bool,
+    ) -> Result<reqwest::Response, ClientError> {
+        self.send_as(request, retryable, false).await
+    }
+
+    /// Send a request, which the API answers without credentials too.
+    /// Credentials are sent anyway, if there are any
+    async fn send_public(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ClientError> {
+        self.send_as(request, true, true).await
+    }
+
+    async fn send_as(
+        &self,
+        request: reqwest::RequestBuilder,
+        retryable: bool,
+        public: bool,
+    ) -> Result<reqwest::Response, ClientError> {
+        if self.settings.cache.offline {
+            return Err(ClientError::Offline);
+        }
+        let response = self.send_with_retry(&request, retryable, public).await?;
+        if response.status() == StatusCode::UNAUTHORIZED && self.refresh_session().await? {
+            return self.send_with_retry(&request, retryable, public).await;
+        }
+        Ok(response)
+    }
+
+    /// Send a request, or answer it from the fixtures, if they are enabled.
+    /// The outer error is a fixture failure, the inner one a transport failure.
+    async fn dispatch(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Result<reqwest::Response, reqwest::Error>, ClientError> {
+        let request = request.build()?;
+        match &self.fixtures {
+            Some(fixtures) => fixtures.exchange(&self.client, request).await,
+            None => Ok(self.client.execute(request).await),
+        }
+    }
+
+    /// Send a request, retrying transient failures if the request may be repeated
+    async fn send_with_retry(
+        &self,
+        request: &reqwest::RequestBuilder,
+        retryable: bool,
+        public: bool,
+    ) -> Result<reqwest::Response, ClientError> {
+        let policy = &self.settings.retry;
+        let max_attempts = if retryable { policy.max_attempts } else { 1 };
+        let mut attempt = 1;
+        loop {
+            let header = match &*self.auth.lock().expect("Auth lock is poisoned") {
+                AuthStrategy::Anonymous if public => None,
+                auth => Some(auth.header()?),
+            };
+            let mut attempt_request = request
+                .try_clone()
+                .expect("Requests without streaming bodies can be cloned");
+            if let Some((name, value)) = header {
+                attempt_request = attempt_request.header(name, value);
+            }
+            let result = self.dispatch(attempt_request).await?;
+            if attempt >= max_attempts {
+                return Ok(result?);
+            }
+            let delay = match &result {
+                Ok(response) if retry::is_retryable_status(response.status()) => {
+                    match retry::response_delay(policy, attempt, response) {
+                        Some(delay) => delay,
+                        None => {
+                            log::warn!("Retry-After exceeds {:?}, not retrying", policy.max_delay);
+                            return Ok(result?);
+                        }
+                    }
+                }
+                Err(err) if retry::is_retryable_error(err) => retry::backoff(policy, attempt),
+                _ => return Ok(result?),
+            };
+            log::warn!(
+                "Request failed (attempt {} of {}), retrying in {:?}",
+                attempt,
+                max_attempts,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn into_payment_addresses(
+    response: PaymentAddressResponse,
+    cached_age: Option<Duration>,
+) -> Result<PaymentAddresses, ClientError> {
+    if let Some(error) = response.error {
+        return Err(ClientError::from_api_error(None, error));
+    }
+    let mut addresses: PaymentAddresses =
+        response.result.unwrap_or_default().into_values().collect();
+    addresses.cached_age = cached_age;
+    Ok(addresses)
+}
+
+fn header_value(response: &reqwest::Response, name: HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
 }
 
 trait ResponseExt {
-    fn assert_success(self) -> Result<reqwest::Response, ClientError>;
+    async fn assert_success(self) -> Result<reqwest::Response, ClientError>;
 }
 
 impl ResponseExt for reqwest::Response {
-    /// Extracts 404 error codes
-    fn assert_success(self) -> Result<reqwest::Response, ClientError> {
+    /// Extracts 404 error codes and API error bodies of failed responses
+    async fn assert_success(self) -> Result<reqwest::Response, ClientError> {
         let status = self.status();
-        if status == reqwest::StatusCode::NOT_FOUND {
+        if status == StatusCode::NOT_FOUND {
             return Err(ClientError::YatNotFound);
         }
-        Ok(self.error_for_status()?)
+        let err = match self.error_for_status_ref() {
+            Ok(_) => return Ok(self),
+            Err(err) => err,
+        };
+        let body = self.bytes().await.unwrap_or_default();
+        match serde_json::from_slice::<ApiErrorBody>(&body) {
+            Ok(body) => Err(ClientError::from_api_error(Some(status), body.into())),
+            Err(_) => Err(err.into()),
+        }
     }
 }
@@ -1,69 +1,337 @@
-use serde::Serialize;
+use std::fmt::{Display, Formatter};
 
-use crate::cli::CreateAddress;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Debug, Eq, PartialEq)]
+use crate::base::{
+    currency::Currency,
+    tag::{Tag, TagError},
+    token::Token,
+};
+
+use super::{payment_data::PaymentData, record::Record};
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct EditRequest {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub insert: Vec<InsertData>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delete: Vec<String>, // Hashes of the records to delete
 }
 
-#[derive(Serialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct InsertData {
     pub data: String,
     pub tag: String,
 }
 
-impl From<CreateAddress> for EditRequest {
-    fn from(create_data: CreateAddress) -> Self {
-        let mut data_parts = vec![create_data.address];
-        if let Some(description) = create_data.description {
-            data_parts.push(description);
+/// An edit request as it would be sent. Credentials are redacted
+#[derive(Debug)]
+pub struct EditPreview<'e> {
+    pub method: Method,
+    pub url: String,
+    pub auth_header: Option<String>, // Name of the authentication header
+    pub body: &'e EditRequest,
+}
+
+impl Display for EditPreview<'_> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        writeln!(fmt, "{} {}", self.method, self.url)?;
+        if let Some(auth_header) = &self.auth_header {
+            writeln!(fmt, "{}: ***", auth_header)?;
+        }
+        writeln!(fmt, "content-type: application/json")?;
+        let body = serde_json::to_string_pretty(self.body).map_err(|_| std::fmt::Error)?;
+        write!(fmt, "\n{}", body)
+    }
+}
+
+/// A payment address to be created
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NewAddress {
+    pub currency: Currency,
+    pub token: Option<Token>, // Set for token addresses, which use the token currency
+    pub address: String,
+    pub description: Option<String>,
+    pub default: bool, // Replaces the current default address of the currency
+}
+
+/// A record of any tag, such as a website redirect or a social handle
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NewRecord {
+    pub tag: Tag,
+    pub data: String,
+}
+
+impl NewRecord {
+    /// Validates and normalizes the data for the tag
+    pub fn new(tag: Tag, data: &str) -> Result<Self, TagError> {
+        let data = tag.validate(data)?;
+        Ok(Self { tag, data })
+    }
+
+    /// Redirect of the yat page to a website
+    pub fn redirect(url: &str) -> Result<Self, TagError> {
+        Self::new(Tag::try_from("redirect")?, url)
+    }
+
+    /// User name on a social network from the tag registry, e.g. `twitter`
+    pub fn social(network: &str, handle: &str) -> Result<Self, TagError> {
+        Self::new(Tag::try_from(network)?, handle)
+    }
+}
+
+/// Existing payment addresses of a currency. The address is required, if the currency has several
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddressSelector {
+    pub currency: Currency,
+    pub address: Option<String>,
+}
+
+/// A change of an existing payment address. Missing values are kept as they are
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddressUpdate {
+    pub selector: AddressSelector,
+    pub new_address: Option<String>,
+    pub description: Option<String>,
+}
+
+impl From<NewAddress> for InsertData {
+    fn from(new_address: NewAddress) -> Self {
+        let data = PaymentData {
+            token: new_address.token,
+            address: new_address.address,
+            description: new_address.description,
+            signature: None,
+            default: new_address.default,
         }
-        let data = data_parts.join("|");
-        let tag = create_data.currency.get_hex_code();
+        .to_string();
+        let tag = new_address.currency.get_hex_code();
+        Self { data, tag }
+    }
+}
+
+impl From<Vec<NewAddress>> for EditRequest {
+    fn from(new_addresses: Vec<NewAddress>) -> Self {
+        Self {
+            insert: new_addresses.into_iter().map(InsertData::from).collect(),
+            delete: vec![],
+        }
+    }
+}
+
+impl From<NewRecord> for InsertData {
+    fn from(new_record: NewRecord) -> Self {
+        Self {
+            data: new_record.data,
+            tag: new_record.tag.get_hex_code(),
+        }
+    }
+}
+
+impl From<Vec<NewRecord>> for EditRequest {
+    fn from(new_records: Vec<NewRecord>) -> Self {
+        Self {
+            insert: new_records.into_iter().map(InsertData::from).collect(),
+            delete: vec![],
+        }
+    }
+}
+
+impl EditRequest {
+    /// Edit request, which removes records with the given hashes
+    pub fn delete(hashes: Vec<String>) -> Self {
         Self {
-            insert: vec![InsertData { data, tag }],
+            insert: vec![],
+            delete: hashes,
         }
     }
+
+    /// Edit request, which rewrites the records of a currency (and token), so that the record
+    /// with the given hash becomes the only default one. Without a hash all defaults are cleared.
+    pub fn set_default(
+        records: &[Record],
+        currency: &Currency,
+        token: Option<&Token>,
+        hash: Option<&str>,
+    ) -> Self {
+        let mut edit_request = Self::delete(vec![]);
+        for record in records
+            .iter()
+            .filter(|r| r.currency().as_ref() == Some(currency))
+        {
+            let mut data = record.payment_data();
+            if data.token.as_ref() != token {
+                continue;
+            }
+            let default = hash == Some(record.hash.as_str());
+            if data.default != default {
+                data.default = default;
+                edit_request.insert.push(InsertData {
+                    data: data.to_string(),
+                    tag: record.tag.clone(),
+                });
+                edit_request.delete.push(record.hash.clone());
+            }
+        }
+        edit_request
+    }
+
+    pub fn extend(&mut self, other: EditRequest) {
+        self.insert.extend(other.insert);
+        self.delete.extend(other.delete);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.insert.is_empty() && self.delete.is_empty()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::base::currency::Currency;
-
     use super::*;
 
+    fn new_address(address: &str, description: Option<&str>) -> NewAddress {
+        NewAddress {
+            currency: Currency::try_from("Cardano").unwrap(),
+            token: None,
+            address: address.to_owned(),
+            description: description.map(|d| d.to_owned()),
+            default: false,
+        }
+    }
+
+    #[test]
+    fn it_converts_from_new_address_without_description() {
+        assert_eq!(
+            InsertData::from(new_address("ADDR", None)),
+            InsertData {
+                data: "ADDR".to_owned(),
+                tag: "0x1012".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn it_converts_from_new_address_with_description() {
+        assert_eq!(
+            InsertData::from(new_address("ADDR", Some("Some description"))),
+            InsertData {
+                data: "ADDR|Some description".to_owned(),
+                tag: "0x1012".to_owned(),
+            }
+        );
+    }
+
     #[test]
-    fn it_converts_from_create_address_without_description() {
+    fn it_inserts_all_new_addresses_in_one_request() {
         assert_eq!(
-            EditRequest::from(CreateAddress {
-                currency: Currency::try_from("Cardano").unwrap(),
-                address: "ADDR".to_owned(),
-                description: None,
-            }),
+            EditRequest::from(vec![
+                new_address("ADDR1", None),
+                new_address("ADDR2", Some("Savings"))
+            ]),
             EditRequest {
-                insert: vec![InsertData {
-                    data: "ADDR".to_owned(),
-                    tag: "0x1012".to_owned(),
-                }]
+                insert: vec![
+                    InsertData {
+                        data: "ADDR1".to_owned(),
+                        tag: "0x1012".to_owned(),
+                    },
+                    InsertData {
+                        data: "ADDR2|Savings".to_owned(),
+                        tag: "0x1012".to_owned(),
+                    }
+                ],
+                delete: vec![],
             }
         );
     }
 
     #[test]
-    fn it_converts_from_create_address_with_description() {
+    fn it_moves_default_flag_to_given_record() {
+        let records = vec![
+            Record {
+                tag: "0x1012".to_owned(),
+                data: "ADDR1|||true".to_owned(),
+                hash: "HASH1".to_owned(),
+            },
+            Record {
+                tag: "0x1012".to_owned(),
+                data: "ADDR2|Savings".to_owned(),
+                hash: "HASH2".to_owned(),
+            },
+            Record {
+                tag: "0x1015".to_owned(),
+                data: "ADDR3|||true".to_owned(),
+                hash: "HASH3".to_owned(),
+            },
+        ];
+        let cardano = Currency::try_from("Cardano").unwrap();
         assert_eq!(
-            EditRequest::from(CreateAddress {
-                currency: Currency::try_from("Cardano").unwrap(),
-                address: "ADDR".to_owned(),
-                description: Some("Some description".to_owned()),
-            }),
+            EditRequest::set_default(&records, &cardano, None, Some("HASH2")),
             EditRequest {
-                insert: vec![InsertData {
-                    data: "ADDR|Some description".to_owned(),
-                    tag: "0x1012".to_owned(),
-                }]
+                insert: vec![
+                    InsertData {
+                        data: "ADDR1".to_owned(),
+                        tag: "0x1012".to_owned(),
+                    },
+                    InsertData {
+                        data: "ADDR2|Savings||true".to_owned(),
+                        tag: "0x1012".to_owned(),
+                    }
+                ],
+                delete: vec!["HASH1".to_owned(), "HASH2".to_owned()],
             }
         );
+        assert!(EditRequest::set_default(&records, &cardano, None, Some("HASH1")).is_empty());
+    }
+
+    #[test]
+    fn it_serializes_only_non_empty_lists() {
+        assert_eq!(
+            serde_json::to_value(EditRequest::delete(vec!["HASH".to_owned()])).unwrap(),
+            serde_json::json!({ "delete": ["HASH"] })
+        );
+    }
+
+    #[test]
+    fn it_converts_from_typed_records() {
+        assert_eq!(
+            EditRequest::from(vec![
+                NewRecord::redirect("https://example.com").unwrap(),
+                NewRecord::social("Twitter", "@yat").unwrap(),
+            ]),
+            EditRequest {
+                insert: vec![
+                    InsertData {
+                        data: "https://example.com".to_owned(),
+                        tag: "0x4001".to_owned(),
+                    },
+                    InsertData {
+                        data: "yat".to_owned(),
+                        tag: "0x4101".to_owned(),
+                    }
+                ],
+                delete: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn it_previews_request_without_credentials() {
+        let edit_request = EditRequest::delete(vec!["HASH1".to_owned()]);
+        let preview = EditPreview {
+            method: Method::PATCH,
+            url: "https://a.y.at/emoji_id/🐱🐶".to_owned(),
+            auth_header: Some("x-api-key".to_owned()),
+            body: &edit_request,
+        };
+        assert_eq!(
+            preview.to_string(),
+            "PATCH https://a.y.at/emoji_id/🐱🐶\n\
+             x-api-key: ***\n\
+             content-type: application/json\n\
+             \n\
+             {\n  \"delete\": [\n    \"HASH1\"\n  ]\n}"
+        );
     }
 }
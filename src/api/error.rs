@@ -1,15 +1,209 @@
-use crate::base::currency::CurrencyError;
+use std::fmt::{Display, Formatter};
+
+use reqwest::StatusCode;
+
+use crate::base::{currency::CurrencyError, tag::TagError, token::TokenError, yat::YatError};
+
+use super::{
+    address_file::AddressFileError, backup::BackupError, manifest::ManifestError,
+    payment_address::ApiError, payment_data::PaymentDataError,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ClientError {
     #[error("Invalid API key")]
     InvalidApiKey,
+    #[error("Not authenticated. Set YAT_API_KEY or run `yat login`")]
+    NotAuthenticated,
+    #[error("Two factor authentication is not supported")]
+    TwoFactorRequired,
+    #[error("Session file error: {0}")]
+    SessionError(String),
+    #[error("Cache error: {0}")]
+    CacheError(String),
+    #[error("The API can not be called in offline mode")]
+    Offline,
+    #[error("Yat is not cached. Look it up without `--offline` first")]
+    NotCached,
     #[error("API error `{0}`")]
-    ReqwestError(#[from] reqwest::Error),
-    #[error("API response error `{code}`: {reason}")]
-    ApiResponseError { code: String, reason: String },
+    ReqwestError(#[source] reqwest::Error),
+    #[error("API request timed out")]
+    Timeout,
+    #[error("HTTP client configuration error: {0}")]
+    HttpConfigError(String),
+    #[error("Fixture error: {0}")]
+    FixtureError(String),
+    #[error("No recorded fixture matches `{0}`")]
+    FixtureMismatch(String),
+    #[error("API response error `{code}`: {reason}{}", format_hint(.kind))]
+    ApiResponseError {
+        status: Option<StatusCode>, // Missing, if the error came with a successful response
+        kind: ApiErrorCode,
+        code: String,
+        reason: String,
+    },
     #[error("Currency error `{0}`")]
     CurrencyError(#[from] CurrencyError),
+    #[error("Token error `{0}`")]
+    TokenError(#[from] TokenError),
+    #[error("Payment data error `{0}`")]
+    PaymentDataError(#[from] PaymentDataError),
+    #[error("Tag error `{0}`")]
+    TagError(#[from] TagError),
     #[error("Yat was not found")]
     YatNotFound,
+    #[error("Invalid yat: {0}")]
+    YatError(#[from] YatError),
+    #[error("No record matches the given currency (or tag) and address (or data)")]
+    NoMatchingRecords,
+    #[error("`{0}` records match, an address (or data) must be specified")]
+    AmbiguousRecords(usize),
+    #[error("Changes need confirmation, but there is no terminal. Pass `--yes` to apply them")]
+    ConfirmationRequired,
+    #[error("Cancelled, nothing was sent")]
+    Cancelled,
+    #[error("`{0}` address(es) have no signature verified by the given key")]
+    UnsignedAddresses(usize),
+    #[error("`{0}` yat(s) could not be checked")]
+    SearchFailed(usize),
+    #[error("`{0}` yat(s) could not be looked up")]
+    LookupFailed(usize),
+    #[error("I/O error `{0}`")]
+    IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    AddressFileError(#[from] AddressFileError),
+    #[error("{0}")]
+    ManifestError(#[from] ManifestError),
+    #[error("{0}")]
+    BackupError(#[from] BackupError),
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return ClientError::Timeout;
+        }
+        ClientError::ReqwestError(err)
+    }
+}
+
+impl ClientError {
+    pub fn from_api_error(status: Option<StatusCode>, error: ApiError) -> Self {
+        ClientError::ApiResponseError {
+            status,
+            kind: ApiErrorCode::new(status, &error.code),
+            code: error.code,
+            reason: error.reason,
+        }
+    }
+}
+
+/// Well known API error codes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiErrorCode {
+    Unauthorized,
+    Forbidden,
+    ValidationFailed,
+    RateLimited,
+    ServerError,
+    Other,
+}
+
+impl ApiErrorCode {
+    /// The code is recognised by its name first, by the HTTP status otherwise
+    pub fn new(status: Option<StatusCode>, code: &str) -> Self {
+        let name: String = code
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_ref() {
+            "unauthorized" | "unauthenticated" | "invalidtoken" => ApiErrorCode::Unauthorized,
+            "forbidden" | "accessdenied" => ApiErrorCode::Forbidden,
+            "validationfailed" | "validation" | "invalidinput" => ApiErrorCode::ValidationFailed,
+            "ratelimited" | "ratelimitexceeded" | "toomanyrequests" => ApiErrorCode::RateLimited,
+            _ => match status {
+                Some(StatusCode::UNAUTHORIZED) => ApiErrorCode::Unauthorized,
+                Some(StatusCode::FORBIDDEN) => ApiErrorCode::Forbidden,
+                Some(StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY) => {
+                    ApiErrorCode::ValidationFailed
+                }
+                Some(StatusCode::TOO_MANY_REQUESTS) => ApiErrorCode::RateLimited,
+                Some(status) if status.is_server_error() => ApiErrorCode::ServerError,
+                _ => ApiErrorCode::Other,
+            },
+        }
+    }
+
+    /// What the user could do about the error
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ApiErrorCode::Unauthorized => {
+                Some("Check YAT_API_KEY or run `yat login` to start a new session")
+            }
+            ApiErrorCode::Forbidden => Some("The account is not allowed to manage this yat"),
+            ApiErrorCode::ValidationFailed => Some("Check the currency, address and description"),
+            ApiErrorCode::RateLimited => Some("Too many requests, try again later"),
+            ApiErrorCode::ServerError => Some("The Yat API is having problems, try again later"),
+            ApiErrorCode::Other => None,
+        }
+    }
+}
+
+impl Display for ApiErrorCode {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{:?}", self)
+    }
+}
+
+fn format_hint(kind: &ApiErrorCode) -> String {
+    kind.hint()
+        .map(|hint| format!(" ({})", hint))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_recognises_codes_by_name() {
+        assert_eq!(
+            ApiErrorCode::new(Some(StatusCode::BAD_REQUEST), "RateLimited"),
+            ApiErrorCode::RateLimited
+        );
+        assert_eq!(
+            ApiErrorCode::new(None, "validation_failed"),
+            ApiErrorCode::ValidationFailed
+        );
+    }
+
+    #[test]
+    fn it_recognises_codes_by_status() {
+        assert_eq!(
+            ApiErrorCode::new(Some(StatusCode::FORBIDDEN), "XXX"),
+            ApiErrorCode::Forbidden
+        );
+        assert_eq!(
+            ApiErrorCode::new(Some(StatusCode::BAD_GATEWAY), "XXX"),
+            ApiErrorCode::ServerError
+        );
+        assert_eq!(ApiErrorCode::new(None, "XXX"), ApiErrorCode::Other);
+    }
+
+    #[test]
+    fn it_displays_hint() {
+        let err = ClientError::from_api_error(
+            Some(StatusCode::UNAUTHORIZED),
+            ApiError {
+                code: "Unauthorized".to_owned(),
+                reason: "Bad token".to_owned(),
+            },
+        );
+        assert_eq!(
+            err.to_string(),
+            "API response error `Unauthorized`: Bad token \
+             (Check YAT_API_KEY or run `yat login` to start a new session)"
+        );
+    }
 }
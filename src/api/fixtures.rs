@@ -0,0 +1,247 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::settings::FixtureMode;
+
+use super::error::ClientError;
+
+static REDACTED: &str = "***";
+
+// Body fields, which are never written to a fixture
+const SECRET_FIELDS: [&str; 5] = [
+    "password",
+    "access_token",
+    "refresh_token",
+    "api_key",
+    "token",
+];
+
+// Response headers, which are not kept. The body is stored decoded and unchunked
+const SKIPPED_HEADERS: [&str; 5] = [
+    "content-length",
+    "content-encoding",
+    "transfer-encoding",
+    "set-cookie",
+    "date",
+];
+
+/// Request part of an exchange. The path is relative to `API_URL`, so that fixtures
+/// recorded against the real API can be replayed against any host
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FixtureRequest {
+    pub method: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FixtureResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Exchange {
+    pub request: FixtureRequest,
+    pub response: FixtureResponse,
+}
+
+/// Records exchanges with the API or replays them instead of calling it
+pub struct Fixtures {
+    mode: FixtureMode,
+    base_url: String,
+    exchanges: Mutex<Vec<(Exchange, bool)>>, // Replayed exchanges are marked as used
+}
+
+impl Fixtures {
+    /// Loads the fixture file. A missing file starts an empty recording
+    pub fn load(mode: &FixtureMode, base_url: &str) -> Result<Self, ClientError> {
+        let path = match mode {
+            FixtureMode::Record(path) | FixtureMode::Replay(path) => path,
+        };
+        let exchanges: Vec<Exchange> = match (mode, fs::read_to_string(path)) {
+            (_, Ok(content)) => {
+                serde_json::from_str(&content).map_err(|err| fixture_error(path.display(), err))?
+            }
+            (FixtureMode::Record(_), Err(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                vec![]
+            }
+            (_, Err(err)) => return Err(fixture_error(path.display(), err)),
+        };
+        Ok(Self {
+            mode: mode.clone(),
+            base_url: base_url.to_owned(),
+            exchanges: Mutex::new(exchanges.into_iter().map(|e| (e, false)).collect()),
+        })
+    }
+
+    /// Answers the request from the fixtures or sends it and records the exchange.
+    /// The outer error is a fixture failure, the inner one a transport failure, which
+    /// may be retried.
+    pub async fn exchange(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<Result<reqwest::Response, reqwest::Error>, ClientError> {
+        let fixture_request = self.fixture_request(&request);
+        let path = match &self.mode {
+            FixtureMode::Replay(_) => return Ok(Ok(self.replay(&fixture_request)?)),
+            FixtureMode::Record(path) => path,
+        };
+
+        let response = match client.execute(request).await {
+            Ok(response) => response,
+            Err(err) => return Ok(Err(err)),
+        };
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(err) => return Ok(Err(err)),
+        };
+        let fixture_response = FixtureResponse {
+            status,
+            headers,
+            body,
+        };
+
+        let mut exchanges = self.lock();
+        exchanges.push((
+            Exchange {
+                request: fixture_request,
+                response: redact_response(&fixture_response),
+            },
+            false,
+        ));
+        save(path, &exchanges)?;
+        Ok(Ok(into_response(&fixture_response)?))
+    }
+
+    /// The first unused exchange matching the request. Repeated requests get the
+    /// recorded responses in order
+    fn replay(&self, request: &FixtureRequest) -> Result<reqwest::Response, ClientError> {
+        let mut exchanges = self.lock();
+        let (exchange, used) = exchanges
+            .iter_mut()
+            .find(|(exchange, used)| !used && exchange.request == *request)
+            .ok_or_else(|| {
+                ClientError::FixtureMismatch(format!("{} {}", request.method, request.path))
+            })?;
+        *used = true;
+        into_response(&exchange.response)
+    }
+
+    fn fixture_request(&self, request: &reqwest::Request) -> FixtureRequest {
+        let url = request.url().as_str();
+        let path = url.strip_prefix(&self.base_url).unwrap_or(url).to_owned();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| match serde_json::from_slice::<Value>(bytes) {
+                Ok(mut json) => {
+                    redact(&mut json);
+                    json
+                }
+                Err(_) => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+            });
+        FixtureRequest {
+            method: request.method().to_string(),
+            path,
+            body,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Exchange, bool)>> {
+        self.exchanges.lock().expect("Fixtures lock is poisoned")
+    }
+}
+
+fn save(path: &Path, exchanges: &[(Exchange, bool)]) -> Result<(), ClientError> {
+    let exchanges: Vec<&Exchange> = exchanges.iter().map(|(exchange, _)| exchange).collect();
+    let content = serde_json::to_string_pretty(&exchanges)
+        .map_err(|err| fixture_error(path.display(), err))?;
+    fs::write(path, content).map_err(|err| fixture_error(path.display(), err))
+}
+
+fn into_response(fixture: &FixtureResponse) -> Result<reqwest::Response, ClientError> {
+    let mut builder = http::Response::builder().status(fixture.status);
+    for (name, value) in &fixture.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(fixture.body.clone())
+        .map_err(|err| fixture_error(fixture.status, err))?;
+    Ok(response.into())
+}
+
+fn redact_response(response: &FixtureResponse) -> FixtureResponse {
+    let body = match serde_json::from_str::<Value>(&response.body) {
+        Ok(mut json) => {
+            redact(&mut json);
+            json.to_string()
+        }
+        Err(_) => response.body.clone(),
+    };
+    FixtureResponse {
+        body,
+        ..response.clone()
+    }
+}
+
+/// Replaces secret values in a JSON document
+fn redact(json: &mut Value) {
+    match json {
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&name.to_lowercase().as_str()) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_owned());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn fixture_error(source: impl std::fmt::Display, err: impl std::fmt::Display) -> ClientError {
+    ClientError::FixtureError(format!("{}: {}", source, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn it_redacts_secret_fields() {
+        let mut body = json!({
+            "email": "user@example.com",
+            "password": "secret",
+            "nested": [{ "refresh_token": "R", "data": "ADDR" }],
+            "error": { "code": "Unauthorized" },
+        });
+        redact(&mut body);
+        assert_eq!(
+            body,
+            json!({
+                "email": "user@example.com",
+                "password": "***",
+                "nested": [{ "refresh_token": "***", "data": "ADDR" }],
+                "error": { "code": "Unauthorized" },
+            })
+        );
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::base::currency::Currency;
+
+use super::{
+    address_file::{format_rows, validate_row, AddressRow, RowError},
+    edit_request::{EditRequest, InsertData, NewAddress},
+    payment_data::PaymentData,
+    record::Record,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error("Cannot read manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Cannot parse manifest: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Manifest lists yat `{0}` more than once")]
+    DuplicateYat(String),
+    #[error("Manifest has invalid addresses of yat `{yat}`:\n{}", format_rows(.errors))]
+    InvalidAddresses { yat: String, errors: Vec<RowError> },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    #[serde(default)]
+    yat: Vec<YatEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct YatEntry {
+    emoji_id: String,
+    #[serde(default)]
+    address: Vec<AddressRow>,
+}
+
+/// All payment addresses a yat should have. Other records, such as social handles, are not
+/// managed by the manifest
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DesiredYat {
+    pub emoji_id: String,
+    pub addresses: Vec<NewAddress>,
+}
+
+/// Desired state of yats, e.g. kept in git:
+///
+/// ```toml
+/// [[yat]]
+/// emoji_id = "🐱🐶"
+///
+/// [[yat.address]]
+/// currency = "Cardano"
+/// address = "addr1..."
+/// description = "Savings"
+/// default = true
+/// ```
+///
+/// Yats missing from the manifest are left alone.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Manifest {
+    pub yats: Vec<DesiredYat>,
+}
+
+/// Reads a TOML manifest
+pub fn read_manifest(path: &Path) -> Result<Manifest, ManifestError> {
+    let content = std::fs::read_to_string(path)?;
+    parse_manifest(&content)
+}
+
+/// Parses and validates a TOML manifest. Addresses are validated as rows of an address file
+pub fn parse_manifest(content: &str) -> Result<Manifest, ManifestError> {
+    let file: ManifestFile = toml::from_str(content)?;
+    let mut yats: Vec<DesiredYat> = vec![];
+    for entry in file.yat {
+        let emoji_id = entry.emoji_id.trim().to_owned();
+        if yats.iter().any(|y| y.emoji_id == emoji_id) {
+            return Err(ManifestError::DuplicateYat(emoji_id));
+        }
+        let addresses = validate_addresses(entry.address).map_err(|errors| {
+            ManifestError::InvalidAddresses {
+                yat: emoji_id.clone(),
+                errors,
+            }
+        })?;
+        yats.push(DesiredYat {
+            emoji_id,
+            addresses,
+        });
+    }
+    Ok(Manifest { yats })
+}
+
+// Besides the rows, an address must be listed once and a currency (or token) must have
+// a single default address
+fn validate_addresses(rows: Vec<AddressRow>) -> Result<Vec<NewAddress>, Vec<RowError>> {
+    let mut addresses: Vec<NewAddress> = vec![];
+    let mut errors = vec![];
+    for (i, row) in rows.into_iter().enumerate() {
+        let address = match validate_row(row) {
+            Ok(address) => address,
+            Err(reason) => {
+                errors.push(RowError { row: i + 1, reason });
+                continue;
+            }
+        };
+        let same_category =
+            |a: &&NewAddress| a.currency == address.currency && a.token == address.token;
+        if addresses
+            .iter()
+            .filter(same_category)
+            .any(|a| a.address == address.address)
+        {
+            let reason = format!("Address `{}` is listed more than once", address.address);
+            errors.push(RowError { row: i + 1, reason });
+        } else if address.default && addresses.iter().filter(same_category).any(|a| a.default) {
+            let reason = "Only one address of a currency (or token) can be the default".to_owned();
+            errors.push(RowError { row: i + 1, reason });
+        }
+        addresses.push(address);
+    }
+    match errors.is_empty() {
+        true => Ok(addresses),
+        false => Err(errors),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanAction {
+    Insert,
+    Update, // Same address with another description or default flag
+    Delete,
+}
+
+/// A change of a single payment address record
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedChange {
+    pub action: PlanAction,
+    pub currency: Currency,
+    pub hash: Option<String>, // Live record, which is deleted or rewritten
+    pub old: Option<PaymentData>,
+    pub new: Option<PaymentData>,
+}
+
+impl PlannedChange {
+    fn data(&self) -> &PaymentData {
+        self.new
+            .as_ref()
+            .or(self.old.as_ref())
+            .expect("A change has at least one side")
+    }
+}
+
+impl Display for PlannedChange {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let data = self.data();
+        let symbol = match self.action {
+            PlanAction::Insert => "+",
+            PlanAction::Update => "~",
+            PlanAction::Delete => "-",
+        };
+        match &data.token {
+            Some(token) => write!(fmt, "{} {} {}", symbol, token, data.address)?,
+            None => write!(
+                fmt,
+                "{} {} {}",
+                symbol,
+                self.currency.get_name(),
+                data.address
+            )?,
+        }
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => {
+                let mut separator = ":";
+                if old.description != new.description {
+                    write!(
+                        fmt,
+                        "{} description: {:?} -> {:?}",
+                        separator,
+                        old.description.as_deref().unwrap_or_default(),
+                        new.description.as_deref().unwrap_or_default()
+                    )?;
+                    separator = ",";
+                }
+                if old.default != new.default {
+                    write!(
+                        fmt,
+                        "{} default: {} -> {}",
+                        separator, old.default, new.default
+                    )?;
+                }
+            }
+            (None, Some(new)) => match (&new.description, new.default) {
+                (Some(description), true) => {
+                    write!(fmt, " (description: {:?}, default)", description)?
+                }
+                (Some(description), false) => write!(fmt, " (description: {:?})", description)?,
+                (None, true) => write!(fmt, " (default)")?,
+                (None, false) => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Changes, which make the live records of a yat match the manifest
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct YatPlan {
+    pub emoji_id: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl YatPlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The single edit request applying the plan. An update rewrites the record
+    pub fn edit_request(&self) -> EditRequest {
+        EditRequest {
+            insert: self
+                .changes
+                .iter()
+                .filter_map(|change| {
+                    change.new.as_ref().map(|data| InsertData {
+                        data: data.to_string(),
+                        tag: change.currency.get_hex_code(),
+                    })
+                })
+                .collect(),
+            delete: self
+                .changes
+                .iter()
+                .filter_map(|change| change.hash.clone())
+                .collect(),
+        }
+    }
+}
+
+impl Display for YatPlan {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}:", self.emoji_id)?;
+        if self.changes.is_empty() {
+            return write!(fmt, "\n  No changes");
+        }
+        for change in &self.changes {
+            write!(fmt, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of changes by action, e.g. `Plan: 1 to add, 0 to change, 2 to destroy.`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlanSummary {
+    pub add: usize,
+    pub change: usize,
+    pub destroy: usize,
+}
+
+impl PlanSummary {
+    pub fn new<'p>(plans: impl IntoIterator<Item = &'p YatPlan>) -> Self {
+        let changes: Vec<&PlannedChange> = plans.into_iter().flat_map(|p| &p.changes).collect();
+        let count = |action: PlanAction| {
+            changes
+                .iter()
+                .filter(|change| change.action == action)
+                .count()
+        };
+        Self {
+            add: count(PlanAction::Insert),
+            change: count(PlanAction::Update),
+            destroy: count(PlanAction::Delete),
+        }
+    }
+}
+
+impl Display for PlanSummary {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "Plan: {} to add, {} to change, {} to destroy.",
+            self.add, self.change, self.destroy
+        )
+    }
+}
+
+fn desired_data(address: &NewAddress) -> PaymentData {
+    PaymentData {
+        token: address.token.clone(),
+        address: address.address.clone(),
+        description: address.description.clone(),
+        signature: None,
+        default: address.default,
+    }
+}
+
+/// Smallest set of changes turning the live payment address records into the desired ones.
+/// Matching records are kept, so are their signatures. Deletions come first, then updates and
+/// insertions in the order of the manifest.
+pub fn plan_addresses(emoji_id: &str, records: &[Record], desired: &[NewAddress]) -> YatPlan {
+    let live: Vec<(&Record, Currency, PaymentData)> = records
+        .iter()
+        .filter_map(|r| Some((r, r.currency()?, r.payment_data())))
+        .collect();
+    let mut matched = vec![false; live.len()];
+    let mut changes = vec![];
+    for address in desired {
+        let found = live.iter().enumerate().find(|(i, (_, currency, data))| {
+            !matched[*i]
+                && *currency == address.currency
+                && data.token == address.token
+                && data.address == address.address
+        });
+        let (i, (record, _, data)) = match found {
+            Some(found) => found,
+            None => {
+                changes.push(PlannedChange {
+                    action: PlanAction::Insert,
+                    currency: address.currency.clone(),
+                    hash: None,
+                    old: None,
+                    new: Some(desired_data(address)),
+                });
+                continue;
+            }
+        };
+        matched[i] = true;
+        if data.description == address.description && data.default == address.default {
+            continue;
+        }
+        changes.push(PlannedChange {
+            action: PlanAction::Update,
+            currency: address.currency.clone(),
+            hash: Some(record.hash.clone()),
+            old: Some(data.clone()),
+            new: Some(PaymentData {
+                signature: data.signature.clone(),
+                ..desired_data(address)
+            }),
+        });
+    }
+
+    let deletions = live
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|((record, currency, data), _)| PlannedChange {
+            action: PlanAction::Delete,
+            currency,
+            hash: Some(record.hash.clone()),
+            old: Some(data),
+            new: None,
+        });
+    YatPlan {
+        emoji_id: emoji_id.to_owned(),
+        changes: deletions.chain(changes).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(data: &str, hash: &str) -> Record {
+        Record {
+            tag: "0x1012".to_owned(),
+            data: data.to_owned(),
+            hash: hash.to_owned(),
+        }
+    }
+
+    fn cardano(address: &str, description: Option<&str>, default: bool) -> NewAddress {
+        NewAddress {
+            currency: Currency::try_from("Cardano").unwrap(),
+            token: None,
+            address: address.to_owned(),
+            description: description.map(|d| d.to_owned()),
+            default,
+        }
+    }
+
+    #[test]
+    fn it_parses_manifest() {
+        let content = r#"
+            [[yat]]
+            emoji_id = "🐱🐶"
+
+            [[yat.address]]
+            currency = "Cardano"
+            address = "ADDR1"
+            description = "Savings"
+            default = true
+
+            [[yat.address]]
+            token = "USDC"
+            network = "Polygon"
+            address = "0xADDR"
+
+            [[yat]]
+            emoji_id = "🐶🐱"
+        "#;
+        let manifest = parse_manifest(content).unwrap();
+        assert_eq!(manifest.yats.len(), 2);
+        assert_eq!(
+            manifest.yats[0].addresses[0],
+            cardano("ADDR1", Some("Savings"), true)
+        );
+        assert_eq!(
+            manifest.yats[0].addresses[1].token.as_ref().unwrap().symbol,
+            "USDC"
+        );
+        assert!(manifest.yats[1].addresses.is_empty());
+    }
+
+    #[test]
+    fn it_rejects_duplicates_and_several_defaults() {
+        let content = r#"
+            [[yat]]
+            emoji_id = "🐱🐶"
+            address = [
+                { currency = "Cardano", address = "ADDR1", default = true },
+                { currency = "Cardano", address = "ADDR1" },
+                { currency = "Cardano", address = "ADDR2", default = true },
+                { currency = "Moon", address = "ADDR3" },
+            ]
+        "#;
+        match parse_manifest(content) {
+            Err(ManifestError::InvalidAddresses { yat, errors }) => {
+                assert_eq!(yat, "🐱🐶");
+                assert_eq!(
+                    errors.iter().map(|e| e.row).collect::<Vec<_>>(),
+                    vec![2, 3, 4]
+                );
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let content = "[[yat]]\nemoji_id = \"🐱🐶\"\n[[yat]]\nemoji_id = \"🐱🐶\"\n";
+        assert!(matches!(
+            parse_manifest(content),
+            Err(ManifestError::DuplicateYat(_))
+        ));
+    }
+
+    #[test]
+    fn it_plans_nothing_when_records_match() {
+        let records = vec![record("ADDR1|Savings||true", "H1")];
+        let plan = plan_addresses("🐱🐶", &records, &[cardano("ADDR1", Some("Savings"), true)]);
+        assert!(plan.is_empty());
+        assert!(plan.edit_request().is_empty());
+        assert_eq!(plan.to_string(), "🐱🐶:\n  No changes");
+    }
+
+    #[test]
+    fn it_plans_inserts_updates_and_deletes() {
+        let records = vec![
+            record("ADDR1|||true", "H1"),
+            record("ADDR2||SIG", "H2"),
+            Record {
+                tag: "0x4001".to_owned(),
+                data: "https://example.com".to_owned(),
+                hash: "H3".to_owned(),
+            },
+        ];
+        let desired = vec![
+            cardano("ADDR2", Some("Savings"), true),
+            cardano("ADDR3", None, false),
+        ];
+        let plan = plan_addresses("🐱🐶", &records, &desired);
+
+        assert_eq!(
+            plan.to_string(),
+            "🐱🐶:\n  - Cardano ADDR1\n  \
+             ~ Cardano ADDR2: description: \"\" -> \"Savings\", default: false -> true\n  \
+             + Cardano ADDR3"
+        );
+        assert_eq!(
+            plan.edit_request(),
+            EditRequest {
+                insert: vec![
+                    InsertData {
+                        data: "ADDR2|Savings|SIG|true".to_owned(),
+                        tag: "0x1012".to_owned(),
+                    },
+                    InsertData {
+                        data: "ADDR3".to_owned(),
+                        tag: "0x1012".to_owned(),
+                    },
+                ],
+                delete: vec!["H1".to_owned(), "H2".to_owned()],
+            }
+        );
+        assert_eq!(
+            PlanSummary::new(&[plan]).to_string(),
+            "Plan: 1 to add, 1 to change, 1 to destroy."
+        );
+    }
+
+    #[test]
+    fn it_deletes_duplicate_live_records() {
+        let records = vec![record("ADDR1", "H1"), record("ADDR1", "H2")];
+        let plan = plan_addresses("🐱🐶", &records, &[cardano("ADDR1", None, false)]);
+        assert_eq!(
+            plan.edit_request(),
+            EditRequest::delete(vec!["H2".to_owned()])
+        );
+    }
+}
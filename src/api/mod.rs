@@ -1,7 +1,20 @@
+pub mod address_file;
+pub mod auth;
+pub mod backup;
+pub mod cache;
 pub mod client;
 pub mod edit_request;
 pub mod error;
+pub mod fixtures;
+pub mod http;
+pub mod manifest;
+pub mod owned_yat;
 pub mod payment_address;
+pub mod payment_data;
+pub mod record;
+pub mod retry;
+pub mod search;
+pub mod watch;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,88 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Deserialize;
+
+use crate::base::emoji::drop_combining_marks;
+
+use super::payment_address::ApiError;
+
+/// Number of yats requested per page
+pub const OWNED_YATS_PAGE_SIZE: usize = 50;
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OwnedYatsResponse {
+    pub error: Option<ApiError>,
+    pub result: Option<Vec<OwnedYat>>,
+    pub paging: Option<Paging>,
+    pub status: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Paging {
+    pub page: usize,
+    pub limit: usize,
+    pub total: usize, // Number of yats on all pages
+}
+
+impl Paging {
+    pub fn is_last_page(&self) -> bool {
+        self.page * self.limit >= self.total
+    }
+}
+
+/// A yat owned by the authenticated account
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OwnedYat {
+    pub emoji_id: String,
+    #[serde(default)]
+    pub locked: bool, // Locked yats can not be edited
+    #[serde(default)]
+    pub record_count: usize,
+}
+
+impl OwnedYat {
+    /// Number of emojis in the yat
+    pub fn length(&self) -> usize {
+        drop_combining_marks(self.emoji_id.chars()).count()
+    }
+}
+
+impl Display for OwnedYat {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "{} (length: {}, {}, records: {})",
+            self.emoji_id,
+            self.length(),
+            if self.locked { "locked" } else { "unlocked" },
+            self.record_count
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_counts_emojis_and_formats_yat() {
+        let yat = OwnedYat {
+            emoji_id: "🐱🐶".to_owned(),
+            locked: true,
+            record_count: 3,
+        };
+        assert_eq!(yat.length(), 2);
+        assert_eq!(yat.to_string(), "🐱🐶 (length: 2, locked, records: 3)");
+    }
+
+    #[test]
+    fn it_detects_last_page() {
+        let paging = |page| Paging {
+            page,
+            limit: 50,
+            total: 120,
+        };
+        assert!(!paging(2).is_last_page());
+        assert!(paging(3).is_last_page());
+    }
+}
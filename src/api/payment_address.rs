@@ -1,26 +1,47 @@
 use std::{
     collections::BTreeMap,
     fmt::{Display, Formatter},
+    time::Duration,
 };
 
-use serde::Deserialize;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
 
 use crate::base::currency::{Currency, CurrencyError};
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+use super::error::ClientError;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ApiError {
     pub code: String,
     pub reason: String,
 }
 
+/// Body of a failed response. The error is either wrapped in an envelope or bare.
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ApiErrorBody {
+    Envelope { error: ApiError },
+    Bare(ApiError),
+}
+
+impl From<ApiErrorBody> for ApiError {
+    fn from(body: ApiErrorBody) -> Self {
+        match body {
+            ApiErrorBody::Envelope { error } => error,
+            ApiErrorBody::Bare(error) => error,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct PaymentAddressResponse {
     pub error: Option<ApiError>,
     pub result: Option<BTreeMap<String, PaymentAddress>>,
     pub status: bool,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct PaymentAddress {
     pub address: String,                    // The payment address.
     pub category: String,                   // The category of this address.
@@ -32,28 +53,184 @@ pub struct PaymentAddress {
     pub signature: Option<String>, // Proof of ownership signature for the address.
 }
 
+/// Ed25519 public key, which signs the payment addresses of a yat
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey([u8; 32]);
+
+impl TryFrom<&str> for PublicKey {
+    type Error = hex::FromHexError;
+
+    /// Hex encoded key
+    fn try_from(key: &str) -> Result<Self, Self::Error> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(key.trim(), &mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
+/// Proof of ownership of a payment address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Unsigned,
+    Unchecked, // Signed, but not verified yet
+    Verified,
+    Invalid,
+}
+
+/// Message signed by the owner of a payment address
+pub fn signing_message(yat: &str, category: &str, address: &str) -> String {
+    format!("{}|{}|{}", yat, category, address)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FriendlyPaymentAddress {
     pub currency: Currency,
     pub address: String,
     pub description: Option<String>,
+    pub default: bool,
+    pub signature: Option<String>,
+    pub signature_status: SignatureStatus,
+    pub short_name: Option<String>,
+    pub long_name: Option<String>,
+    pub settlement_network: Option<String>,
+}
+
+impl FriendlyPaymentAddress {
+    pub fn is_token(&self) -> bool {
+        self.currency.is_token()
+    }
+
+    /// Checks the hex encoded signature locally
+    pub fn verify_signature(&mut self, yat: &str, key: &PublicKey) {
+        let signature = match &self.signature {
+            Some(signature) => signature,
+            None => return,
+        };
+        let message = signing_message(yat, &self.currency.get_hex_code(), &self.address);
+        let verified = hex::decode(signature.trim()).is_ok_and(|signature| {
+            UnparsedPublicKey::new(&ED25519, key.0)
+                .verify(message.as_bytes(), &signature)
+                .is_ok()
+        });
+        self.signature_status = match verified {
+            true => SignatureStatus::Verified,
+            false => SignatureStatus::Invalid,
+        };
+    }
+
+    // Tokens are named by their symbol and network, currencies by their name
+    fn title(&self) -> String {
+        match (self.is_token(), &self.short_name, &self.settlement_network) {
+            (true, Some(symbol), Some(network)) => format!("{} on {}", symbol, network),
+            (true, Some(symbol), None) => symbol.clone(),
+            _ => self.currency.get_name().to_owned(),
+        }
+    }
 }
 
 impl Display for FriendlyPaymentAddress {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        writeln!(
-            fmt,
-            "-------------- {} --------------",
-            self.currency.get_name()
-        )?;
+        writeln!(fmt, "-------------- {} --------------", self.title())?;
         writeln!(fmt, "Currency code: {}", self.currency.get_hex_code())?;
         if let Some(description) = &self.description {
             writeln!(fmt, "Description: {}", description)?;
         }
+        if self.default {
+            writeln!(fmt, "Default: yes")?;
+        }
+        if let Some(short_name) = &self.short_name {
+            writeln!(fmt, "Token: {}", short_name)?;
+        }
+        if let Some(long_name) = &self.long_name {
+            writeln!(fmt, "Token name: {}", long_name)?;
+        }
+        if let Some(settlement_network) = &self.settlement_network {
+            writeln!(fmt, "Settlement network: {}", settlement_network)?;
+        }
+        match (&self.signature, self.signature_status) {
+            (Some(signature), SignatureStatus::Verified) => {
+                writeln!(fmt, "Signature: {} (verified)", signature)?
+            }
+            (Some(signature), SignatureStatus::Invalid) => {
+                writeln!(fmt, "Signature: {} (INVALID)", signature)?
+            }
+            (Some(signature), _) => writeln!(fmt, "Signature: {}", signature)?,
+            (None, _) => {}
+        }
         writeln!(fmt, "Address: {}", self.address)
     }
 }
 
+/// A payment address of a category, which is missing from the known currencies
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPaymentAddress(pub PaymentAddress);
+
+impl Display for UnknownPaymentAddress {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let address = &self.0;
+        writeln!(
+            fmt,
+            "-------------- Unknown ({}) --------------",
+            address.category
+        )?;
+        writeln!(fmt, "Currency code: {}", address.category)?;
+        if let Some(description) = &address.description {
+            writeln!(fmt, "Description: {}", description)?;
+        }
+        if address.default {
+            writeln!(fmt, "Default: yes")?;
+        }
+        writeln!(fmt, "Address: {}", address.address)
+    }
+}
+
+/// Lookup result, which keeps addresses of unknown categories instead of failing
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PaymentAddresses {
+    pub known: Vec<FriendlyPaymentAddress>,
+    pub unknown: Vec<UnknownPaymentAddress>,
+    pub cached_age: Option<Duration>, // Set, if the addresses were served from the cache
+}
+
+/// Result of a single yat within a bulk lookup
+#[derive(Debug)]
+pub struct YatLookup {
+    pub yat: String,
+    pub result: Result<PaymentAddresses, ClientError>,
+}
+
+impl FromIterator<PaymentAddress> for PaymentAddresses {
+    fn from_iter<T: IntoIterator<Item = PaymentAddress>>(iter: T) -> Self {
+        let mut addresses = PaymentAddresses::default();
+        for address in iter {
+            match FriendlyPaymentAddress::try_from(address.clone()) {
+                Ok(known) => addresses.known.push(known),
+                Err(_) => addresses.unknown.push(UnknownPaymentAddress(address)),
+            }
+        }
+        addresses
+    }
+}
+
+impl PaymentAddresses {
+    /// Checks signatures of all known addresses against the public key of the yat
+    pub fn verify_signatures(&mut self, yat: &str, key: &PublicKey) {
+        for address in &mut self.known {
+            address.verify_signature(yat, key);
+        }
+    }
+
+    /// Fails on the first address of an unknown category
+    pub fn strict(self) -> Result<Vec<FriendlyPaymentAddress>, CurrencyError> {
+        match self.unknown.into_iter().next() {
+            Some(UnknownPaymentAddress(address)) => {
+                Err(Currency::try_from(address.category.as_ref()).expect_err("Category is unknown"))
+            }
+            None => Ok(self.known),
+        }
+    }
+}
+
 impl TryFrom<PaymentAddress> for FriendlyPaymentAddress {
     type Error = CurrencyError;
 
@@ -62,6 +239,155 @@ impl TryFrom<PaymentAddress> for FriendlyPaymentAddress {
             currency: Currency::try_from(address.category.as_ref())?,
             address: address.address,
             description: address.description,
+            default: address.default,
+            signature_status: match address.signature {
+                Some(_) => SignatureStatus::Unchecked,
+                None => SignatureStatus::Unsigned,
+            },
+            signature: address.signature,
+            short_name: address.short_name,
+            long_name: address.long_name,
+            settlement_network: address.settlement_network,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use super::*;
+
+    fn payment_address() -> PaymentAddress {
+        PaymentAddress {
+            address: "ADDR".to_owned(),
+            category: "0x1004".to_owned(),
+            default: true,
+            description: Some("Wallet".to_owned()),
+            long_name: Some("USD Coin".to_owned()),
+            settlement_network: Some("Polygon".to_owned()),
+            short_name: Some("USDC".to_owned()),
+            signature: Some("SIG".to_owned()),
+        }
+    }
+
+    #[test]
+    fn it_separates_unknown_categories() {
+        let unknown = PaymentAddress {
+            category: "0x9999".to_owned(),
+            ..payment_address()
+        };
+        let addresses: PaymentAddresses = vec![payment_address(), unknown.clone()]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            addresses.known,
+            vec![FriendlyPaymentAddress::try_from(payment_address()).unwrap()]
+        );
+        assert_eq!(addresses.unknown, vec![UnknownPaymentAddress(unknown)]);
+        assert_eq!(
+            addresses.strict(),
+            Err(CurrencyError::MissingCurrencyCode(0x9999))
+        );
+    }
+
+    #[test]
+    fn it_displays_unknown_address_with_raw_tag() {
+        let unknown = UnknownPaymentAddress(PaymentAddress {
+            category: "0x9999".to_owned(),
+            default: false,
+            description: None,
+            ..payment_address()
+        });
+        assert_eq!(
+            unknown.to_string(),
+            "-------------- Unknown (0x9999) --------------\n\
+             Currency code: 0x9999\n\
+             Address: ADDR\n"
+        );
+    }
+
+    #[test]
+    fn it_keeps_all_fields() {
+        let address = FriendlyPaymentAddress::try_from(payment_address()).unwrap();
+        assert!(address.default);
+        assert_eq!(address.signature.as_deref(), Some("SIG"));
+        assert_eq!(address.short_name.as_deref(), Some("USDC"));
+        assert_eq!(address.long_name.as_deref(), Some("USD Coin"));
+        assert_eq!(address.settlement_network.as_deref(), Some("Polygon"));
+    }
+
+    #[test]
+    fn it_displays_all_fields() {
+        let address = FriendlyPaymentAddress::try_from(payment_address()).unwrap();
+        assert_eq!(
+            address.to_string(),
+            "-------------- Ethereum address --------------\n\
+             Currency code: 0x1004\n\
+             Description: Wallet\n\
+             Default: yes\n\
+             Token: USDC\n\
+             Token name: USD Coin\n\
+             Settlement network: Polygon\n\
+             Signature: SIG\n\
+             Address: ADDR\n"
+        );
+    }
+
+    #[test]
+    fn it_titles_token_by_symbol_and_network() {
+        let address = FriendlyPaymentAddress::try_from(PaymentAddress {
+            category: "0x6300".to_owned(),
+            ..payment_address()
         })
+        .unwrap();
+        assert!(address.is_token());
+        assert!(address
+            .to_string()
+            .starts_with("-------------- USDC on Polygon --------------\n"));
+    }
+
+    fn signed_address(key_pair: &Ed25519KeyPair, message: &str) -> FriendlyPaymentAddress {
+        let signature = hex::encode(key_pair.sign(message.as_bytes()));
+        FriendlyPaymentAddress::try_from(PaymentAddress {
+            signature: Some(signature),
+            ..payment_address()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn it_verifies_signatures() {
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+        let key = PublicKey::try_from(hex::encode(key_pair.public_key()).as_str()).unwrap();
+
+        let mut signed = signed_address(&key_pair, &signing_message("🐱🐶", "0x1004", "ADDR"));
+        assert_eq!(signed.signature_status, SignatureStatus::Unchecked);
+        signed.verify_signature("🐱🐶", &key);
+        assert_eq!(signed.signature_status, SignatureStatus::Verified);
+
+        // Signed for another yat
+        let mut forged = signed_address(&key_pair, &signing_message("🐶🐱", "0x1004", "ADDR"));
+        forged.verify_signature("🐱🐶", &key);
+        assert_eq!(forged.signature_status, SignatureStatus::Invalid);
+
+        let mut unsigned = FriendlyPaymentAddress::try_from(PaymentAddress {
+            signature: None,
+            ..payment_address()
+        })
+        .unwrap();
+        unsigned.verify_signature("🐱🐶", &key);
+        assert_eq!(unsigned.signature_status, SignatureStatus::Unsigned);
+    }
+
+    #[test]
+    fn it_fails_on_invalid_signature_encoding_or_key() {
+        let key = PublicKey::try_from("00".repeat(32).as_str()).unwrap();
+        let mut address = FriendlyPaymentAddress::try_from(payment_address()).unwrap();
+        address.verify_signature("🐱🐶", &key);
+        assert_eq!(address.signature_status, SignatureStatus::Invalid);
+
+        assert!(PublicKey::try_from("ABCD").is_err());
     }
 }
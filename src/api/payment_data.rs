@@ -0,0 +1,224 @@
+use std::fmt::{Display, Formatter};
+
+use crate::base::token::Token;
+
+static SEPARATOR: char = '|';
+static DEFAULT_FLAG: &str = "true";
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PaymentDataError {
+    #[error("Address is empty")]
+    EmptyAddress,
+    #[error("Address must not contain `|`")]
+    SeparatorInAddress,
+    #[error("Description must not contain `|`")]
+    SeparatorInDescription,
+}
+
+/// Checks values entered by the user. A separator would shift the fields after it,
+/// e.g. turn part of a description into a signature or the default flag
+pub fn check_fields(address: &str, description: Option<&str>) -> Result<(), PaymentDataError> {
+    if address.is_empty() {
+        return Err(PaymentDataError::EmptyAddress);
+    }
+    if address.contains(SEPARATOR) {
+        return Err(PaymentDataError::SeparatorInAddress);
+    }
+    if description.is_some_and(|d| d.contains(SEPARATOR)) {
+        return Err(PaymentDataError::SeparatorInDescription);
+    }
+    Ok(())
+}
+
+/// Data of a payment address record, stored as `address|description|signature|default`.
+/// Token records are prefixed with `symbol|name|network|` and may end with `|contract`, so that
+/// other readers of the record still find the address in place. Trailing empty fields are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PaymentData {
+    pub token: Option<Token>,
+    pub address: String,
+    pub description: Option<String>,
+    pub signature: Option<String>,
+    pub default: bool,
+}
+
+impl From<&str> for PaymentData {
+    fn from(data: &str) -> Self {
+        Self::parse(data, false)
+    }
+}
+
+impl PaymentData {
+    /// Parses record data. Token records carry the token in front of the address
+    pub fn parse(data: &str, token: bool) -> Self {
+        // Token records have the token in front and the contract at the end
+        let (token_fields, trailing_fields) = if token { (3, 1) } else { (0, 0) };
+        let mut parts = data.splitn(token_fields + 4 + trailing_fields, SEPARATOR);
+        let token = token.then(|| {
+            let mut next_field = || parts.next().unwrap_or_default().to_owned();
+            Token {
+                symbol: next_field(),
+                name: next_field(),
+                network: next_field(),
+                contract: None,
+            }
+        });
+        let address = parts.next().unwrap_or_default().to_owned();
+        let mut next_field = || parts.next().filter(|d| !d.is_empty()).map(|d| d.to_owned());
+        let description = next_field();
+        let signature = next_field();
+        let default = next_field().as_deref() == Some(DEFAULT_FLAG);
+        let contract = next_field();
+        let token = token.map(|token| Token { contract, ..token });
+        Self {
+            token,
+            address,
+            description,
+            signature,
+            default,
+        }
+    }
+}
+
+impl Display for PaymentData {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut fields = vec![];
+        if let Some(token) = &self.token {
+            fields.extend([
+                token.symbol.as_str(),
+                token.name.as_str(),
+                token.network.as_str(),
+            ]);
+        }
+        let token_fields = fields.len();
+        fields.extend([
+            self.address.as_str(),
+            self.description.as_deref().unwrap_or_default(),
+            self.signature.as_deref().unwrap_or_default(),
+            if self.default { DEFAULT_FLAG } else { "" },
+        ]);
+        if let Some(contract) = self.token.as_ref().and_then(|t| t.contract.as_deref()) {
+            fields.push(contract);
+        }
+        while fields.len() > token_fields + 1 && fields.last() == Some(&"") {
+            fields.pop();
+        }
+        write!(fmt, "{}", fields.join(&SEPARATOR.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_checks_fields() {
+        assert_eq!(check_fields("ADDR", Some("Savings")), Ok(()));
+        assert_eq!(check_fields("", None), Err(PaymentDataError::EmptyAddress));
+        assert_eq!(
+            check_fields("ADDR|x", None),
+            Err(PaymentDataError::SeparatorInAddress)
+        );
+        assert_eq!(
+            check_fields("ADDR", Some("x||true")),
+            Err(PaymentDataError::SeparatorInDescription)
+        );
+    }
+
+    #[test]
+    fn it_parses_address_without_description() {
+        assert_eq!(
+            PaymentData::from("ADDR"),
+            PaymentData {
+                address: "ADDR".to_owned(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_address_with_description() {
+        assert_eq!(
+            PaymentData::from("ADDR|Some description"),
+            PaymentData {
+                address: "ADDR".to_owned(),
+                description: Some("Some description".to_owned()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_default_flag() {
+        assert_eq!(
+            PaymentData::from("ADDR|||true"),
+            PaymentData {
+                address: "ADDR".to_owned(),
+                default: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn it_formats_data() {
+        let data = PaymentData {
+            address: "ADDR".to_owned(),
+            description: Some("Some description".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(data.to_string(), "ADDR|Some description");
+        assert_eq!(PaymentData::from(data.to_string().as_ref()), data);
+    }
+
+    #[test]
+    fn it_formats_default_flag_with_empty_fields() {
+        let data = PaymentData {
+            address: "ADDR".to_owned(),
+            signature: Some("SIG".to_owned()),
+            default: true,
+            ..Default::default()
+        };
+        assert_eq!(data.to_string(), "ADDR||SIG|true");
+        assert_eq!(PaymentData::from(data.to_string().as_ref()), data);
+    }
+
+    #[test]
+    fn it_formats_and_parses_token_data() {
+        let data = PaymentData {
+            token: Some(Token::new("USDC", "Ethereum", None).unwrap()),
+            address: "0xADDR".to_owned(),
+            description: Some("Payroll".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(data.to_string(), "USDC|USD Coin|Ethereum|0xADDR|Payroll");
+        assert_eq!(PaymentData::parse(&data.to_string(), true), data);
+
+        let data = PaymentData {
+            token: Some(Token::new("PYUSD", "Ethereum", Some("0xC0NTRACT")).unwrap()),
+            address: "0xADDR".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            data.to_string(),
+            "PYUSD|PYUSD|Ethereum|0xADDR||||0xC0NTRACT"
+        );
+        assert_eq!(PaymentData::parse(&data.to_string(), true), data);
+    }
+
+    #[test]
+    fn it_parses_token_data_without_contract() {
+        // Layout written before contracts were stored
+        let data = PaymentData::parse("USDC|USD Coin|Ethereum|0xADDR|Payroll|SIG|true", true);
+        assert_eq!(
+            data,
+            PaymentData {
+                token: Some(Token::new("USDC", "Ethereum", None).unwrap()),
+                address: "0xADDR".to_owned(),
+                description: Some("Payroll".to_owned()),
+                signature: Some("SIG".to_owned()),
+                default: true,
+            }
+        );
+    }
+}
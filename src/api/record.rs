@@ -0,0 +1,175 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::base::{
+    currency::Currency,
+    tag::{Tag, TagError},
+};
+
+use super::{error::ClientError, payment_address::ApiError, payment_data::PaymentData};
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RecordsResponse {
+    pub error: Option<ApiError>,
+    pub result: Option<Vec<Record>>,
+    pub status: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub tag: String,  // Hex code of the record type
+    pub data: String, // Raw record data
+    pub hash: String, // Record hash, used to delete the record
+}
+
+impl Record {
+    /// Currency of the record, if it is a payment address record
+    pub fn currency(&self) -> Option<Currency> {
+        Currency::try_from(self.tag.as_ref()).ok()
+    }
+
+    /// Record data parsed as payment address data
+    pub fn payment_data(&self) -> PaymentData {
+        let token = self.currency().is_some_and(|c| c.is_token());
+        PaymentData::parse(&self.data, token)
+    }
+
+    /// Type of the record, e.g. a payment currency or a social network
+    pub fn tag(&self) -> Result<Tag, TagError> {
+        Tag::try_from(self.tag.as_str())
+    }
+
+    fn matches(&self, currency: &Currency, address: Option<&str>) -> bool {
+        self.currency().as_ref() == Some(currency)
+            && address.is_none_or(|address| self.payment_data().address == address)
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.tag() {
+            Ok(tag) => write!(fmt, "{}: {}", tag, self.data),
+            Err(_) => write!(fmt, "{}: {}", self.tag, self.data),
+        }
+    }
+}
+
+/// Finds payment address records of the given currency.
+/// If no address is given, the currency must have a single record, so that we never
+/// remove more than asked for.
+pub fn find_payment_records<'r>(
+    records: &'r [Record],
+    currency: &Currency,
+    address: Option<&str>,
+) -> Result<Vec<&'r Record>, ClientError> {
+    let found: Vec<&Record> = records
+        .iter()
+        .filter(|r| r.matches(currency, address))
+        .collect();
+    select(found, address.is_some())
+}
+
+/// Finds records of any tag. If no data is given, the tag must have a single record
+pub fn find_records<'r>(
+    records: &'r [Record],
+    tag: &Tag,
+    data: Option<&str>,
+) -> Result<Vec<&'r Record>, ClientError> {
+    let found: Vec<&Record> = records
+        .iter()
+        .filter(|r| r.tag().as_ref() == Ok(tag))
+        .filter(|r| data.is_none_or(|data| r.data == data))
+        .collect();
+    select(found, data.is_some())
+}
+
+fn select(found: Vec<&Record>, narrowed: bool) -> Result<Vec<&Record>, ClientError> {
+    match found.len() {
+        0 => Err(ClientError::NoMatchingRecords),
+        1 => Ok(found),
+        n if !narrowed => Err(ClientError::AmbiguousRecords(n)),
+        _ => Ok(found),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(tag: &str, data: &str, hash: &str) -> Record {
+        Record {
+            tag: tag.to_owned(),
+            data: data.to_owned(),
+            hash: hash.to_owned(),
+        }
+    }
+
+    fn cardano() -> Currency {
+        Currency::try_from("Cardano").unwrap()
+    }
+
+    #[test]
+    fn it_finds_single_record_by_currency() {
+        let records = vec![
+            record("0x1012", "ADDR1", "H1"),
+            record("0x1015", "ADDR2", "H2"),
+        ];
+        let found = find_payment_records(&records, &cardano(), None).unwrap();
+        assert_eq!(found, vec![&records[0]]);
+    }
+
+    #[test]
+    fn it_finds_records_by_currency_and_address() {
+        let records = vec![
+            record("0x1012", "ADDR1", "H1"),
+            record("0x1012", "ADDR2|Desc", "H2"),
+        ];
+        let found = find_payment_records(&records, &cardano(), Some("ADDR2")).unwrap();
+        assert_eq!(found, vec![&records[1]]);
+    }
+
+    #[test]
+    fn it_fails_if_nothing_matches() {
+        let records = vec![record("0x1015", "ADDR1", "H1")];
+        assert!(matches!(
+            find_payment_records(&records, &cardano(), None),
+            Err(ClientError::NoMatchingRecords)
+        ));
+    }
+
+    #[test]
+    fn it_fails_if_address_is_required_to_disambiguate() {
+        let records = vec![
+            record("0x1012", "ADDR1", "H1"),
+            record("0x1012", "ADDR2", "H2"),
+        ];
+        assert!(matches!(
+            find_payment_records(&records, &cardano(), None),
+            Err(ClientError::AmbiguousRecords(2))
+        ));
+    }
+
+    #[test]
+    fn it_finds_records_by_tag_and_data() {
+        let records = vec![
+            record("0x4101", "yat", "H1"),
+            record("0x4001", "https://a.example", "H2"),
+            record("0x4001", "https://b.example", "H3"),
+        ];
+        let twitter = Tag::try_from("twitter").unwrap();
+        let redirect = Tag::try_from("redirect").unwrap();
+        assert_eq!(
+            find_records(&records, &twitter, None).unwrap(),
+            vec![&records[0]]
+        );
+        assert_eq!(
+            find_records(&records, &redirect, Some("https://b.example")).unwrap(),
+            vec![&records[2]]
+        );
+        assert!(matches!(
+            find_records(&records, &redirect, None),
+            Err(ClientError::AmbiguousRecords(2))
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+
+use crate::settings::RetryPolicy;
+
+/// Transient failures, which are worth another attempt
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Connection failures (refused, reset, timed out), which are worth another attempt
+pub fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout() || err.is_request()
+}
+
+/// Delay before the next attempt after a failed response.
+/// `Retry-After` is honoured on 429 and 503, exponential backoff is used otherwise.
+/// Missing, if the server asks to wait longer than `max_delay`, so that the error is surfaced.
+pub fn response_delay(policy: &RetryPolicy, attempt: u32, response: &Response) -> Option<Duration> {
+    let status = response.status();
+    let retry_after = match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_retry_after(v, SystemTime::now())),
+        _ => None,
+    };
+    match retry_after {
+        Some(delay) => (delay <= policy.max_delay).then_some(delay),
+        None => Some(backoff(policy, attempt)),
+    }
+}
+
+/// Exponential backoff for the given (1-based) attempt, capped by `max_delay`.
+/// With jitter enabled, a random delay between half and full backoff is used.
+pub fn backoff(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    let delay = policy
+        .base_delay
+        .saturating_mul(factor)
+        .min(policy.max_delay);
+    if !policy.jitter || delay.is_zero() {
+        return delay;
+    }
+    let half = delay / 2;
+    half + rand::thread_rng().gen_range(Duration::ZERO..=delay - half)
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value.trim()).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: bool) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            jitter,
+            retry_patch: false,
+        }
+    }
+
+    #[test]
+    fn it_doubles_backoff_until_cap() {
+        let policy = policy(false);
+        assert_eq!(backoff(&policy, 1), Duration::from_millis(100));
+        assert_eq!(backoff(&policy, 2), Duration::from_millis(200));
+        assert_eq!(backoff(&policy, 4), Duration::from_millis(800));
+        assert_eq!(backoff(&policy, 5), Duration::from_millis(1000));
+        assert_eq!(backoff(&policy, 40), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn it_keeps_jittered_backoff_within_bounds() {
+        let policy = policy(true);
+        for _ in 0..100 {
+            let delay = backoff(&policy, 3);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+        }
+    }
+
+    #[test]
+    fn it_parses_retry_after_seconds() {
+        assert_eq!(
+            parse_retry_after("7", SystemTime::now()),
+            Some(Duration::from_secs(7))
+        );
+    }
+
+    #[test]
+    fn it_parses_retry_after_date() {
+        let now = httpdate::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:47 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:27 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn it_ignores_invalid_retry_after() {
+        assert_eq!(parse_retry_after("soon", SystemTime::now()), None);
+    }
+
+    #[test]
+    fn it_retries_only_transient_statuses() {
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::OK));
+    }
+}
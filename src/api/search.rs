@@ -0,0 +1,107 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Deserialize;
+
+use super::payment_address::ApiError;
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SearchResponse {
+    pub error: Option<ApiError>,
+    pub result: Option<SearchResult>,
+    #[serde(default)]
+    pub alternates: Vec<SearchResult>, // Suggestions, e.g. for a taken or partly specified yat
+    pub status: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    Taken,
+    Reserved,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Availability and price of a yat
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    pub emoji_id: String,
+    pub availability: Availability,
+    pub price: Option<u64>, // In cents
+}
+
+/// A searched yat with suggested alternatives
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct YatSearch {
+    pub result: SearchResult,
+    pub alternates: Vec<SearchResult>,
+}
+
+impl Display for Availability {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let availability = match self {
+            Availability::Available => "available",
+            Availability::Taken => "taken",
+            Availability::Reserved => "reserved",
+            Availability::Unknown => "unknown",
+        };
+        write!(fmt, "{}", availability)
+    }
+}
+
+impl Display for SearchResult {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}: {}", self.emoji_id, self.availability)?;
+        if let Some(price) = self.price {
+            write!(fmt, ", ${}.{:02}", price / 100, price % 100)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for YatSearch {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}", self.result)?;
+        if !self.alternates.is_empty() {
+            write!(fmt, "\nSuggestions:")?;
+        }
+        for alternate in &self.alternates {
+            write!(fmt, "\n  {}", alternate)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(availability: Availability, price: Option<u64>) -> SearchResult {
+        SearchResult {
+            emoji_id: "🐱🐶".to_owned(),
+            availability,
+            price,
+        }
+    }
+
+    #[test]
+    fn it_formats_price_in_dollars() {
+        assert_eq!(
+            result(Availability::Available, Some(12005)).to_string(),
+            "🐱🐶: available, $120.05"
+        );
+        assert_eq!(result(Availability::Taken, None).to_string(), "🐱🐶: taken");
+    }
+
+    #[test]
+    fn it_lists_suggestions() {
+        let search = YatSearch {
+            result: result(Availability::Reserved, None),
+            alternates: vec![result(Availability::Available, Some(500))],
+        };
+        assert_eq!(
+            search.to_string(),
+            "🐱🐶: reserved\nSuggestions:\n  🐱🐶: available, $5.00"
+        );
+    }
+}
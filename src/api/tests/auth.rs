@@ -0,0 +1,155 @@
+use std::path::Path;
+
+use httpmock::prelude::*;
+use serde_json::json;
+
+use crate::{
+    api::{
+        auth::{load_session, save_session, Session},
+        client::ApiClient,
+        error::ClientError,
+    },
+    settings::Settings,
+};
+
+use super::{build_settings, yat};
+
+fn session_settings(server: &MockServer, session_file: &Path) -> Settings {
+    Settings {
+        yat_api_key: None,
+        session_file: Some(session_file.to_owned()),
+        ..build_settings(server)
+    }
+}
+
+fn session(access_token: &str, refresh_token: &str) -> Session {
+    Session {
+        access_token: access_token.to_owned(),
+        refresh_token: refresh_token.to_owned(),
+        email: None,
+    }
+}
+
+#[tokio::test]
+async fn it_fails_without_credentials() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let settings = session_settings(&server, &dir.path().join("session.json"));
+    let yat = yat(&settings);
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(matches!(result.unwrap_err(), ClientError::NotAuthenticated));
+}
+
+#[tokio::test]
+async fn it_logs_in_and_stores_session() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let session_file = dir.path().join("session.json");
+    let settings = session_settings(&server, &session_file);
+    let yat = yat(&settings);
+
+    let login_mock = server.mock(|when, then| {
+        when.method(POST).path("/auth/token").json_body(json!({
+          "email": "user@example.com",
+          "password": "secret"
+        }));
+        then.status(200).json_body(json!({
+          "access_token": "ACCESS",
+          "refresh_token": "REFRESH"
+        }));
+    });
+    let lookup_mock = server.mock(|when, then| {
+        when.method(GET).header("authorization", "Bearer ACCESS");
+        then.status(200)
+            .json_body(json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    api.login("user@example.com".to_owned(), "secret".to_owned())
+        .await
+        .unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(result.is_ok());
+    assert_eq!(
+        load_session(&session_file).unwrap(),
+        Some(Session {
+            email: Some("user@example.com".to_owned()),
+            ..session("ACCESS", "REFRESH")
+        })
+    );
+
+    login_mock.assert();
+    lookup_mock.assert();
+}
+
+#[tokio::test]
+async fn it_refreshes_expired_session() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let session_file = dir.path().join("session.json");
+    save_session(&session_file, &session("OLD", "REFRESH")).unwrap();
+    let settings = session_settings(&server, &session_file);
+    let yat = yat(&settings);
+
+    let expired_mock = server.mock(|when, then| {
+        when.method(GET).header("authorization", "Bearer OLD");
+        then.status(401);
+    });
+    let refresh_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/auth/token/refresh")
+            .json_body(json!({ "refresh_token": "REFRESH" }));
+        then.status(200).json_body(json!({
+          "access_token": "NEW",
+          "refresh_token": "REFRESH2"
+        }));
+    });
+    let lookup_mock = server.mock(|when, then| {
+        when.method(GET).header("authorization", "Bearer NEW");
+        then.status(200)
+            .json_body(json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(result.is_ok());
+    assert_eq!(
+        load_session(&session_file).unwrap(),
+        Some(session("NEW", "REFRESH2"))
+    );
+
+    expired_mock.assert();
+    refresh_mock.assert();
+    lookup_mock.assert();
+}
+
+#[tokio::test]
+async fn it_prefers_api_key_over_session() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let session_file = dir.path().join("session.json");
+    save_session(&session_file, &session("ACCESS", "REFRESH")).unwrap();
+    let settings = Settings {
+        yat_api_key: Some("KEY".to_owned()),
+        ..session_settings(&server, &session_file)
+    };
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).header("x-api-key", "KEY");
+        then.status(200)
+            .json_body(json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(result.is_ok());
+
+    mock.assert();
+}
@@ -0,0 +1,44 @@
+use crate::{
+    api::{backup::Backup, client::ApiClient, edit_request::NewRecord},
+    mock_server::MockYatServer,
+};
+
+use super::{settings_for, yat};
+
+#[tokio::test]
+async fn it_restores_exported_records() {
+    let server = MockYatServer::start("KEY").await.unwrap();
+    let settings = settings_for(server.base_url());
+    let yat = yat(&settings);
+    server.add_yat(yat.as_ref());
+    let api = ApiClient::new(&settings).unwrap();
+
+    api.add_records(
+        &yat,
+        vec![NewRecord::redirect("https://old.example").unwrap()],
+    )
+    .await
+    .unwrap();
+    let backup = api.export(&yat).await.unwrap();
+    assert_eq!(backup.yat, yat.as_ref());
+    assert_eq!(backup.api_url, settings.api_url);
+    assert_eq!(backup.records.len(), 1);
+
+    api.set_record(&yat, NewRecord::redirect("https://new.example").unwrap())
+        .await
+        .unwrap();
+    api.add_records(&yat, vec![NewRecord::social("twitter", "yat").unwrap()])
+        .await
+        .unwrap();
+
+    let backup = Backup::parse(&backup.to_json()).unwrap();
+    let plan = api.plan_restore(&backup).await.unwrap();
+    assert_eq!(plan.delete.len(), 2);
+    assert_eq!(plan.insert.len(), 1);
+    api.apply_edit(&yat, &plan.edit_request()).await.unwrap();
+
+    let records = api.fetch_records(&yat).await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].data, "https://old.example");
+    assert!(api.plan_restore(&backup).await.unwrap().is_empty());
+}
@@ -0,0 +1,254 @@
+use std::{path::Path, time::Duration};
+
+use httpmock::prelude::*;
+use httpmock::Method::PATCH;
+use serde_json::json;
+
+use crate::{
+    api::{
+        auth::{save_session, Session},
+        client::ApiClient,
+        edit_request::NewAddress,
+        error::ClientError,
+    },
+    base::currency::Currency,
+    settings::{CacheSettings, Settings},
+};
+
+use super::{build_settings, yat};
+
+fn cache_settings(server: &MockServer, dir: &Path, ttl: Duration, offline: bool) -> Settings {
+    Settings {
+        cache: CacheSettings {
+            dir: Some(dir.to_owned()),
+            ttl,
+            offline,
+        },
+        ..build_settings(server)
+    }
+}
+
+fn lookup_body() -> serde_json::Value {
+    json!({
+      "result": {
+        "0x1012": { "address": "ADDR", "category": "0x1012", "default": true }
+      },
+      "status": true
+    })
+}
+
+#[tokio::test]
+async fn it_serves_fresh_entries_from_cache() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let settings = cache_settings(&server, dir.path(), Duration::from_secs(60), false);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200).json_body(lookup_body());
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let first = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+    let second = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+
+    assert_eq!(first.cached_age, None);
+    assert!(second.cached_age.is_some());
+    assert_eq!(first.known, second.known);
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_revalidates_expired_entries() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let settings = cache_settings(&server, dir.path(), Duration::ZERO, false);
+    let yat = yat(&settings);
+
+    let not_modified_mock = server.mock(|when, then| {
+        when.method(GET)
+            .header("if-none-match", "\"v1\"")
+            .header("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT");
+        then.status(304);
+    });
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200)
+            .header("ETag", "\"v1\"")
+            .header("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT")
+            .json_body(lookup_body());
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let first = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+    let second = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(second.known.len(), 1);
+
+    mock.assert();
+    not_modified_mock.assert();
+}
+
+#[tokio::test]
+async fn it_serves_only_cached_entries_offline() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let online = cache_settings(&server, dir.path(), Duration::ZERO, false);
+    let offline = cache_settings(&server, dir.path(), Duration::ZERO, true);
+    let yat = yat(&online);
+
+    let offline_api = ApiClient::new(&offline).unwrap();
+    assert!(matches!(
+        offline_api.fetch_payment_addresses_lenient(&yat).await,
+        Err(ClientError::NotCached)
+    ));
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200).json_body(lookup_body());
+    });
+    ApiClient::new(&online)
+        .unwrap()
+        .fetch_payment_addresses_lenient(&yat)
+        .await
+        .unwrap();
+
+    let result = offline_api.fetch_payment_addresses_lenient(&yat).await;
+
+    assert!(result.unwrap().cached_age.is_some());
+    assert!(matches!(
+        offline_api.fetch_records(&yat).await,
+        Err(ClientError::Offline)
+    ));
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_removes_entry_after_edit() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let settings = cache_settings(&server, dir.path(), Duration::from_secs(60), false);
+    let yat = yat(&settings);
+
+    let lookup_mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200).json_body(lookup_body());
+    });
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+    api.create_payment_addresses(
+        &yat,
+        vec![NewAddress {
+            currency: Currency::try_from("Cardano").unwrap(),
+            token: None,
+            address: "ADDR2".to_owned(),
+            description: None,
+            default: false,
+        }],
+    )
+    .await
+    .unwrap();
+    let result = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+
+    assert_eq!(result.cached_age, None);
+
+    lookup_mock.assert_hits(2);
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_does_not_share_entries_between_apis() {
+    let production = MockServer::start();
+    let staging = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let production_settings =
+        cache_settings(&production, dir.path(), Duration::from_secs(60), false);
+    let staging_settings = cache_settings(&staging, dir.path(), Duration::from_secs(60), false);
+    let yat = yat(&production_settings);
+
+    let production_mock = production.mock(|when, then| {
+        when.method(GET);
+        then.status(200).json_body(lookup_body());
+    });
+    let staging_mock = staging.mock(|when, then| {
+        when.method(GET);
+        then.status(200).json_body(json!({
+          "result": {
+            "0x1012": { "address": "STAGING", "category": "0x1012", "default": true }
+          },
+          "status": true
+        }));
+    });
+
+    let production_api = ApiClient::new(&production_settings).unwrap();
+    production_api
+        .fetch_payment_addresses_lenient(&yat)
+        .await
+        .unwrap();
+    let staging_api = ApiClient::new(&staging_settings).unwrap();
+    let result = staging_api
+        .fetch_payment_addresses_lenient(&yat)
+        .await
+        .unwrap();
+
+    assert_eq!(result.cached_age, None);
+    assert_eq!(result.known[0].address, "STAGING");
+
+    production_mock.assert();
+    staging_mock.assert();
+}
+
+#[tokio::test]
+async fn it_does_not_share_entries_between_sessions() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let cache_dir = dir.path().join("cache");
+    let yat = yat(&build_settings(&server));
+
+    let alice_mock = server.mock(|when, then| {
+        when.method(GET).header("authorization", "Bearer ALICE");
+        then.status(200).json_body(lookup_body());
+    });
+    let bob_mock = server.mock(|when, then| {
+        when.method(GET).header("authorization", "Bearer BOB");
+        then.status(200).json_body(json!({
+          "result": {
+            "0x1012": { "address": "BOB", "category": "0x1012", "default": true }
+          },
+          "status": true
+        }));
+    });
+
+    for (name, access_token, address) in [("alice", "ALICE", "ADDR"), ("bob", "BOB", "BOB")] {
+        let session_file = dir.path().join(format!("{}.json", name));
+        let session = Session {
+            access_token: access_token.to_owned(),
+            refresh_token: "REFRESH".to_owned(),
+            email: Some(format!("{}@example.com", name)),
+        };
+        save_session(&session_file, &session).unwrap();
+        let settings = Settings {
+            yat_api_key: None,
+            session_file: Some(session_file),
+            ..cache_settings(&server, &cache_dir, Duration::from_secs(60), false)
+        };
+
+        let api = ApiClient::new(&settings).unwrap();
+        let result = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+
+        assert_eq!(result.cached_age, None);
+        assert_eq!(result.known[0].address, address);
+    }
+
+    alice_mock.assert();
+    bob_mock.assert();
+}
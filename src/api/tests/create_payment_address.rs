@@ -4,29 +4,24 @@ use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde_json::json;
 
 use crate::{
-    api::{client::ApiClient, error::ClientError},
-    base::{currency::Currency, yat::Yat},
-    cli::CreateAddress,
-    settings::Settings,
+    api::{
+        client::ApiClient,
+        edit_request::NewAddress,
+        error::{ApiErrorCode, ClientError},
+        payment_data::PaymentDataError,
+    },
+    base::{currency::Currency, token::Token},
 };
 
-fn build_settings(server: &MockServer) -> Settings {
-    Settings {
-        api_url: server.base_url(),
-        yat_api_key: "KEY".to_owned(),
-        required_yat_length: 2..=3,
-    }
-}
+use super::{build_settings, yat};
 
-fn yat(settings: &Settings) -> Yat {
-    Yat::new(settings, "💪💪💪").unwrap()
-}
-
-fn create_address() -> CreateAddress {
-    CreateAddress {
+fn new_address(address: &str) -> NewAddress {
+    NewAddress {
         currency: Currency::try_from("Cardano").unwrap(),
-        address: "ADDR".to_owned(),
+        token: None,
+        address: address.to_owned(),
         description: None,
+        default: false,
     }
 }
 
@@ -42,8 +37,10 @@ async fn it_fails_on_404_status_code() {
         then.status(404);
     });
 
-    let api = ApiClient::new(&settings);
-    let result = api.create_payment_address(&yat, create_address()).await;
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address("ADDR")])
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), ClientError::YatNotFound));
@@ -63,8 +60,10 @@ async fn it_fails_on_other_failed_status_code() {
         then.status(500);
     });
 
-    let api = ApiClient::new(&settings);
-    let result = api.create_payment_address(&yat, create_address()).await;
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address("ADDR")])
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), ClientError::ReqwestError(_)));
@@ -95,10 +94,155 @@ async fn it_succeeds_on_positive_response() {
         then.status(200);
     });
 
-    let api = ApiClient::new(&settings);
-    let result = api.create_payment_address(&yat, create_address()).await;
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address("ADDR")])
+        .await;
+
+    assert!(result.is_ok());
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_creates_many_addresses_in_one_request() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH).json_body(json!({
+          "insert": [
+            { "data": "ADDR1", "tag": "0x1012" },
+            { "data": "ADDR2", "tag": "0x1012" }
+          ]
+        }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address("ADDR1"), new_address("ADDR2")])
+        .await;
 
     assert!(result.is_ok());
 
     mock.assert();
 }
+
+#[tokio::test]
+async fn it_fails_with_bare_api_error() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(422).json_body(json!({
+          "code": "ValidationFailed",
+          "reason": "Invalid address"
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address("ADDR")])
+        .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::ApiResponseError { kind: ApiErrorCode::ValidationFailed, reason, .. }
+          if reason == "Invalid address"
+    ));
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_creates_token_address() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH).json_body(json!({
+          "insert": [
+            { "data": "USDT|Tether USD|TRON|TADDR", "tag": "0x6300" }
+          ]
+        }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(
+            &yat,
+            vec![NewAddress {
+                currency: Token::currency(),
+                token: Some(Token::new("USDT", "tron", None).unwrap()),
+                address: "TADDR".to_owned(),
+                description: None,
+                default: false,
+            }],
+        )
+        .await;
+
+    assert!(result.is_ok());
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_plans_creation_without_sending() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let edit_request = api
+        .plan_create_payment_addresses(&yat, vec![new_address("ADDR")])
+        .await
+        .unwrap();
+    let preview = api.preview_edit(&yat, &edit_request).to_string();
+
+    assert!(preview.starts_with(&format!(
+        "PATCH {}/emoji_id/{}\nx-api-key: ***\n",
+        server.base_url(),
+        yat.as_ref()
+    )));
+    assert!(preview.contains("\"data\": \"ADDR\""));
+    assert!(!preview.contains("KEY"));
+
+    mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn it_rejects_separator_in_description() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let address = NewAddress {
+        description: Some("x||true".to_owned()),
+        ..new_address("ADDR")
+    };
+    let result = api.create_payment_addresses(&yat, vec![address]).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::PaymentDataError(PaymentDataError::SeparatorInDescription)
+    ));
+
+    mock.assert_hits(0);
+}
@@ -0,0 +1,140 @@
+use httpmock::prelude::*;
+use httpmock::Method::PATCH;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::json;
+
+use crate::{
+    api::{client::ApiClient, edit_request::AddressSelector, error::ClientError},
+    base::{currency::Currency, yat::Yat},
+};
+
+use super::{build_settings, yat};
+
+fn path(yat: &Yat) -> String {
+    format!(
+        "/emoji_id/{}",
+        utf8_percent_encode(yat.as_ref(), NON_ALPHANUMERIC)
+    )
+}
+
+fn delete_address(address: Option<&str>) -> AddressSelector {
+    AddressSelector {
+        currency: Currency::try_from("Cardano").unwrap(),
+        address: address.map(|a| a.to_owned()),
+    }
+}
+
+fn mock_records<'a>(server: &'a MockServer, yat: &Yat) -> httpmock::Mock<'a> {
+    server.mock(|when, then| {
+        when.method(GET)
+            .path(path(yat))
+            .header("accept", "application/json")
+            .header("x-api-key", "KEY");
+        then.status(200).json_body(json!({
+          "result": [
+            { "tag": "0x1012", "data": "ADDR1|Description1", "hash": "HASH1" },
+            { "tag": "0x1012", "data": "ADDR2", "hash": "HASH2" },
+            { "tag": "0x1015", "data": "ADDR3", "hash": "HASH3" },
+          ],
+          "status": true
+        }));
+    })
+}
+
+#[tokio::test]
+async fn it_fails_on_404_status_code() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new(r"^/emoji_id/.+").unwrap());
+        then.status(404);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.delete_payment_address(&yat, delete_address(None)).await;
+
+    assert!(result.is_err());
+    assert!(matches!(result.unwrap_err(), ClientError::YatNotFound));
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_fails_if_no_record_matches() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let delete_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .delete_payment_address(&yat, delete_address(Some("ADDR3")))
+        .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::NoMatchingRecords
+    ));
+
+    records_mock.assert();
+    delete_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn it_fails_if_address_is_ambiguous() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let delete_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.delete_payment_address(&yat, delete_address(None)).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::AmbiguousRecords(2)
+    ));
+
+    records_mock.assert();
+    delete_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn it_deletes_only_matching_record() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let delete_mock = server.mock(|when, then| {
+        when.method(PATCH)
+            .path(path(&yat))
+            .header("accept", "application/json")
+            .header("x-api-key", "KEY")
+            .json_body(json!({ "delete": ["HASH1"] }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .delete_payment_address(&yat, delete_address(Some("ADDR1")))
+        .await;
+
+    assert!(result.is_ok());
+
+    records_mock.assert();
+    delete_mock.assert();
+}
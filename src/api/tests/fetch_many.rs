@@ -0,0 +1,80 @@
+use httpmock::prelude::*;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::json;
+
+use crate::{
+    api::{client::ApiClient, error::ClientError},
+    base::yat::YatError,
+};
+
+use super::build_settings;
+
+fn path(yat: &str) -> String {
+    format!(
+        "/emoji_id/{}/payment",
+        utf8_percent_encode(yat, NON_ALPHANUMERIC)
+    )
+}
+
+#[tokio::test]
+async fn it_keeps_separate_result_for_each_yat() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+
+    let found_mock = server.mock(|when, then| {
+        when.method(GET).path(path("💪💪💪"));
+        then.status(200).json_body(json!({
+          "result": {
+            "0x1012": {
+              "address": "ADDR",
+              "category": "0x1012",
+              "default": false
+            }
+          },
+          "status": true
+        }));
+    });
+    let missing_mock = server.mock(|when, then| {
+        when.method(GET).path(path("🐱🐱"));
+        then.status(404);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let yats = ["💪💪💪", "💪", "🐱🐱"].map(str::to_owned);
+    let lookups = api.fetch_many(yats, 2).await;
+
+    assert_eq!(
+        lookups.iter().map(|l| l.yat.as_str()).collect::<Vec<_>>(),
+        vec!["💪💪💪", "💪", "🐱🐱"]
+    );
+    assert_eq!(lookups[0].result.as_ref().unwrap().known.len(), 1);
+    assert!(matches!(
+        lookups[1].result,
+        Err(ClientError::YatError(YatError::InvalidLength))
+    ));
+    assert!(matches!(lookups[2].result, Err(ClientError::YatNotFound)));
+
+    found_mock.assert();
+    missing_mock.assert();
+}
+
+#[tokio::test]
+async fn it_treats_zero_concurrency_as_one() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200)
+            .json_body(json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let lookups = api
+        .fetch_many(vec!["💪💪💪".to_owned(), "🐱🐱".to_owned()], 0)
+        .await;
+
+    assert!(lookups.iter().all(|l| l.result.is_ok()));
+
+    mock.assert_hits(2);
+}
@@ -1,24 +1,18 @@
 use httpmock::prelude::*;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::StatusCode;
 use serde_json::json;
 
 use crate::{
-    api::{client::ApiClient, error::ClientError, payment_address::FriendlyPaymentAddress},
-    base::{currency::Currency, yat::Yat},
-    settings::Settings,
+    api::{
+        client::ApiClient,
+        error::{ApiErrorCode, ClientError},
+        payment_address::{FriendlyPaymentAddress, SignatureStatus},
+    },
+    base::currency::Currency,
 };
 
-fn build_settings(server: &MockServer) -> Settings {
-    Settings {
-        api_url: server.base_url(),
-        yat_api_key: "KEY".to_owned(),
-        required_yat_length: 2..=3,
-    }
-}
-
-fn yat(settings: &Settings) -> Yat {
-    Yat::new(settings, "💪💪💪").unwrap()
-}
+use super::{build_settings, yat};
 
 #[tokio::test]
 async fn it_fails_on_404_status_code() {
@@ -32,7 +26,7 @@ async fn it_fails_on_404_status_code() {
         then.status(404);
     });
 
-    let api = ApiClient::new(&settings);
+    let api = ApiClient::new(&settings).unwrap();
     let result = api.fetch_payment_addresses(&yat).await;
 
     assert!(result.is_err());
@@ -53,7 +47,7 @@ async fn it_fails_on_other_failed_status_code() {
         then.status(500);
     });
 
-    let api = ApiClient::new(&settings);
+    let api = ApiClient::new(&settings).unwrap();
     let result = api.fetch_payment_addresses(&yat).await;
 
     assert!(result.is_err());
@@ -74,7 +68,7 @@ async fn it_fails_on_invalid_json() {
         then.status(200).body("INVALID");
     });
 
-    let api = ApiClient::new(&settings);
+    let api = ApiClient::new(&settings).unwrap();
     let result = api.fetch_payment_addresses(&yat).await;
 
     assert!(result.is_err());
@@ -98,13 +92,13 @@ async fn it_fails_with_api_response_error_if_api_has_it() {
         }));
     });
 
-    let api = ApiClient::new(&settings);
+    let api = ApiClient::new(&settings).unwrap();
     let result = api.fetch_payment_addresses(&yat).await;
 
     assert!(result.is_err());
     assert!(matches!(
         result.unwrap_err(),
-        ClientError::ApiResponseError { code, reason }
+        ClientError::ApiResponseError { status: None, code, reason, .. }
           if code == "XXX" && reason == "FAILURE"
     ));
 
@@ -132,7 +126,7 @@ async fn it_fails_on_unknown_categories() {
         }));
     });
 
-    let api = ApiClient::new(&settings);
+    let api = ApiClient::new(&settings).unwrap();
     let result = api.fetch_payment_addresses(&yat).await;
 
     assert!(result.is_err());
@@ -174,7 +168,7 @@ async fn it_succeeds_on_proper_response() {
         }));
     });
 
-    let api = ApiClient::new(&settings);
+    let api = ApiClient::new(&settings).unwrap();
     let result = api.fetch_payment_addresses(&yat).await;
 
     assert_eq!(
@@ -184,14 +178,120 @@ async fn it_succeeds_on_proper_response() {
                 currency: Currency::try_from(0x1012).unwrap(),
                 address: "Address1".to_owned(),
                 description: Some("Description1".to_owned()),
+                default: false,
+                signature: None,
+                signature_status: SignatureStatus::Unsigned,
+                short_name: None,
+                long_name: None,
+                settlement_network: None,
             },
             FriendlyPaymentAddress {
                 currency: Currency::try_from(0x1015).unwrap(),
                 address: "Address2".to_owned(),
                 description: None,
+                default: false,
+                signature: None,
+                signature_status: SignatureStatus::Unsigned,
+                short_name: None,
+                long_name: None,
+                settlement_network: None,
             }
         ]
     );
 
     mock.assert();
 }
+
+#[tokio::test]
+async fn it_keeps_default_flag() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new(r"^/emoji_id/.+/payment").unwrap());
+        then.status(200).json_body(json!({
+          "result": {
+            "0x1012": { "address": "Address1", "category": "0x1012", "default": true },
+            "0x1015": { "address": "Address2", "category": "0x1015", "default": false }
+          },
+          "status": true
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await.unwrap();
+
+    assert_eq!(
+        result.iter().map(|a| a.default).collect::<Vec<_>>(),
+        vec![true, false]
+    );
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_keeps_unknown_categories_in_lenient_mode() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new(r"^/emoji_id/.+/payment").unwrap());
+        then.status(200).json_body(json!({
+          "result": {
+            "0x1012": {
+              "address": "Address1",
+              "category": "0x1012",
+              "default": false
+            },
+            "0x9999": {
+              "address": "Address2",
+              "category": "0x9999",
+              "default": false
+            },
+          },
+          "status": true
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses_lenient(&yat).await.unwrap();
+
+    assert_eq!(result.known.len(), 1);
+    assert_eq!(result.known[0].address, "Address1");
+    assert_eq!(result.unknown.len(), 1);
+    assert_eq!(result.unknown[0].0.category, "0x9999");
+    assert_eq!(result.unknown[0].0.address, "Address2");
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_fails_with_typed_api_error_on_failed_status_code() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new(r"^/emoji_id/.+/payment").unwrap());
+        then.status(403).json_body(json!({
+          "error": { "code": "XXX", "reason": "Not yours" },
+          "status": false
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::ApiResponseError { status: Some(StatusCode::FORBIDDEN), kind: ApiErrorCode::Forbidden, code, reason }
+          if code == "XXX" && reason == "Not yours"
+    ));
+
+    mock.assert();
+}
@@ -0,0 +1,182 @@
+use std::{fs, path::Path};
+
+use httpmock::prelude::*;
+use serde_json::json;
+
+use crate::{
+    api::{client::ApiClient, error::ClientError},
+    settings::{FixtureMode, HttpSettings, Settings},
+};
+
+use super::{build_settings, settings_for, yat};
+
+fn with_fixtures(settings: Settings, mode: FixtureMode) -> Settings {
+    Settings {
+        http: HttpSettings {
+            fixtures: Some(mode),
+            ..Default::default()
+        },
+        ..settings
+    }
+}
+
+fn mock_lookup(server: &MockServer) -> httpmock::Mock<'_> {
+    server.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new(r"^/emoji_id/.+/payment$").unwrap());
+        then.status(200).header("etag", "\"v1\"").json_body(json!({
+          "result": {
+            "0x1012": { "address": "ADDR", "category": "0x1012", "default": true }
+          },
+          "status": true
+        }));
+    })
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+}
+
+#[tokio::test]
+async fn it_records_and_replays_without_network() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixtures.json");
+    let mock = mock_lookup(&server);
+
+    let settings = with_fixtures(build_settings(&server), FixtureMode::Record(path.clone()));
+    let yat = yat(&settings);
+    let recorded = ApiClient::new(&settings)
+        .unwrap()
+        .fetch_payment_addresses(&yat)
+        .await
+        .unwrap();
+    mock.assert();
+
+    let content = read(&path);
+    assert!(content.contains("\"path\": \"/emoji_id/"));
+    assert!(content.contains("\"etag\": \"\\\"v1\\\"\""));
+    assert!(!content.contains("KEY"));
+
+    // Nothing listens on the replay host
+    let settings = with_fixtures(
+        settings_for("http://127.0.0.1:9".to_owned()),
+        FixtureMode::Replay(path),
+    );
+    let api = ApiClient::new(&settings).unwrap();
+    let replayed = api.fetch_payment_addresses(&yat).await.unwrap();
+    assert_eq!(replayed, recorded);
+
+    // Every exchange is replayed once
+    assert!(matches!(
+        api.fetch_payment_addresses(&yat).await.unwrap_err(),
+        ClientError::FixtureMismatch(_)
+    ));
+}
+
+#[tokio::test]
+async fn it_redacts_credentials() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixtures.json");
+    let login_mock = server.mock(|when, then| {
+        when.method(POST).path("/auth/token");
+        then.status(200).json_body(json!({
+          "access_token": "ACCESS",
+          "refresh_token": "REFRESH"
+        }));
+    });
+
+    let settings = with_fixtures(
+        Settings {
+            yat_api_key: None,
+            ..build_settings(&server)
+        },
+        FixtureMode::Record(path.clone()),
+    );
+    let api = ApiClient::new(&settings).unwrap();
+    api.login("user@example.com".to_owned(), "secret".to_owned())
+        .await
+        .unwrap();
+    login_mock.assert();
+
+    let content = read(&path);
+    assert!(content.contains("user@example.com"));
+    for secret in ["secret", "ACCESS", "REFRESH"] {
+        assert!(!content.contains(secret), "{} was recorded", secret);
+    }
+}
+
+#[tokio::test]
+async fn it_keeps_session_file_when_replaying_login() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixtures.json");
+    let session_file = dir.path().join("session.json");
+    server.mock(|when, then| {
+        when.method(POST).path("/auth/token");
+        then.status(200).json_body(json!({
+          "access_token": "ACCESS",
+          "refresh_token": "REFRESH"
+        }));
+    });
+    let settings = |mode| {
+        with_fixtures(
+            Settings {
+                yat_api_key: None,
+                session_file: Some(session_file.clone()),
+                ..build_settings(&server)
+            },
+            mode,
+        )
+    };
+
+    ApiClient::new(&settings(FixtureMode::Record(path.clone())))
+        .unwrap()
+        .login("user@example.com".to_owned(), "secret".to_owned())
+        .await
+        .unwrap();
+    let stored = read(&session_file);
+
+    ApiClient::new(&settings(FixtureMode::Replay(path)))
+        .unwrap()
+        .login("user@example.com".to_owned(), "secret".to_owned())
+        .await
+        .unwrap();
+
+    assert_eq!(read(&session_file), stored);
+    assert!(stored.contains("ACCESS"));
+}
+
+#[tokio::test]
+async fn it_fails_on_unmatched_request() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixtures.json");
+    fs::write(&path, "[]").unwrap();
+
+    let settings = with_fixtures(
+        settings_for("http://127.0.0.1:9".to_owned()),
+        FixtureMode::Replay(path),
+    );
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_records(&yat(&settings)).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::FixtureMismatch(request) if request.starts_with("GET /emoji_id/")
+    ));
+}
+
+#[tokio::test]
+async fn it_fails_on_missing_replay_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings = with_fixtures(
+        settings_for("http://127.0.0.1:9".to_owned()),
+        FixtureMode::Replay(dir.path().join("missing.json")),
+    );
+
+    assert!(matches!(
+        ApiClient::new(&settings),
+        Err(ClientError::FixtureError(_))
+    ));
+}
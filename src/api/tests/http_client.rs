@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use httpmock::prelude::*;
+
+use crate::{
+    api::{client::ApiClient, error::ClientError},
+    settings::{HttpSettings, Settings},
+};
+
+use super::{build_settings, yat};
+
+fn http_settings(server: &MockServer, timeout: Duration) -> Settings {
+    Settings {
+        http: HttpSettings {
+            timeout,
+            user_agent: "yat-test".to_owned(),
+            ..Default::default()
+        },
+        ..build_settings(server)
+    }
+}
+
+#[tokio::test]
+async fn it_fails_with_timeout_on_slow_response() {
+    let server = MockServer::start();
+    let settings = http_settings(&server, Duration::from_millis(100));
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200).delay(Duration::from_millis(500));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(matches!(result.unwrap_err(), ClientError::Timeout));
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_reuses_client_with_configured_user_agent() {
+    let server = MockServer::start();
+    let settings = http_settings(&server, Duration::from_secs(5));
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).header("user-agent", "yat-test");
+        then.status(200)
+            .json_body(serde_json::json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    for _ in 0..2 {
+        assert!(api.fetch_payment_addresses(&yat).await.unwrap().is_empty());
+    }
+
+    mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn it_sends_requests_through_proxy() {
+    let server = MockServer::start();
+    let settings = Settings {
+        api_url: "http://yat.invalid".to_owned(),
+        http: HttpSettings {
+            proxy: Some(server.base_url()),
+            ..Default::default()
+        },
+        ..build_settings(&server)
+    };
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).header("host", "yat.invalid");
+        then.status(200)
+            .json_body(serde_json::json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(result.is_ok());
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_bypasses_proxy_for_no_proxy_hosts() {
+    let server = MockServer::start();
+    let settings = Settings {
+        http: HttpSettings {
+            proxy: Some("http://127.0.0.1:1".to_owned()),
+            no_proxy: vec!["127.0.0.1".to_owned()],
+            ..Default::default()
+        },
+        ..build_settings(&server)
+    };
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200)
+            .json_body(serde_json::json!({ "result": {}, "status": true }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(result.is_ok());
+
+    mock.assert();
+}
+
+#[test]
+fn it_fails_on_invalid_ca_bundle() {
+    let server = MockServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let ca_bundle = dir.path().join("ca.pem");
+    std::fs::write(&ca_bundle, "not a certificate").unwrap();
+    let settings = Settings {
+        http: HttpSettings {
+            ca_bundle: Some(ca_bundle),
+            ..Default::default()
+        },
+        ..build_settings(&server)
+    };
+
+    assert!(matches!(
+        ApiClient::new(&settings),
+        Err(ClientError::HttpConfigError(_))
+    ));
+}
@@ -0,0 +1,94 @@
+use httpmock::prelude::*;
+use serde_json::json;
+
+use crate::api::{client::ApiClient, error::ClientError};
+
+use super::build_settings;
+
+#[tokio::test]
+async fn it_pages_through_owned_yats() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+
+    let first_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/emoji_id")
+            .query_param("page", "1")
+            .query_param("limit", "50")
+            .header("x-api-key", "KEY");
+        then.status(200).json_body(json!({
+          "result": [
+            { "emoji_id": "🐱🐶", "locked": true, "record_count": 3 },
+          ],
+          "paging": { "page": 1, "limit": 50, "total": 51 },
+          "status": true
+        }));
+    });
+    let second_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/emoji_id")
+            .query_param("page", "2")
+            .query_param("limit", "50");
+        then.status(200).json_body(json!({
+          "result": [{ "emoji_id": "🚀🌙🌟" }],
+          "paging": { "page": 2, "limit": 50, "total": 51 },
+          "status": true
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let yats = api.list_owned_yats().await.unwrap();
+
+    assert_eq!(yats.len(), 2);
+    assert_eq!(yats[0].to_string(), "🐱🐶 (length: 2, locked, records: 3)");
+    assert_eq!(
+        yats[1].to_string(),
+        "🚀🌙🌟 (length: 3, unlocked, records: 0)"
+    );
+
+    first_page.assert();
+    second_page.assert();
+}
+
+#[tokio::test]
+async fn it_stops_on_short_page_without_paging() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/emoji_id").query_param("page", "1");
+        then.status(200).json_body(json!({
+          "result": [{ "emoji_id": "🐱🐶" }],
+          "status": true
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let yats = api.list_owned_yats().await.unwrap();
+
+    assert_eq!(yats.len(), 1);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_fails_on_api_error() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/emoji_id");
+        then.status(200).json_body(json!({
+          "error": { "code": "Unauthorized", "reason": "No access" },
+          "status": false
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.list_owned_yats().await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::ApiResponseError { .. }
+    ));
+    mock.assert();
+}
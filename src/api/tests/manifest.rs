@@ -0,0 +1,101 @@
+use httpmock::prelude::*;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::json;
+
+use crate::{
+    api::{
+        client::ApiClient,
+        edit_request::{EditRequest, InsertData},
+        manifest::{parse_manifest, PlanAction},
+    },
+    mock_server::MockYatServer,
+};
+
+use super::{build_settings, settings_for, yat};
+
+const MANIFEST: &str = r#"
+    [[yat]]
+    emoji_id = "💪💪💪"
+
+    [[yat.address]]
+    currency = "Cardano"
+    address = "ADDR1"
+    description = "Savings"
+    default = true
+
+    [[yat.address]]
+    currency = "Cardano"
+    address = "ADDR3"
+"#;
+
+#[tokio::test]
+async fn it_plans_manifest_against_live_records() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = server.mock(|when, then| {
+        when.method(GET).path(format!(
+            "/emoji_id/{}",
+            utf8_percent_encode(yat.as_ref(), NON_ALPHANUMERIC)
+        ));
+        then.status(200).json_body(json!({
+          "result": [
+            { "tag": "0x1012", "data": "ADDR1|||true", "hash": "HASH1" },
+            { "tag": "0x1012", "data": "ADDR2", "hash": "HASH2" },
+            { "tag": "0x4101", "data": "yat", "hash": "HASH3" },
+          ],
+          "status": true
+        }));
+    });
+
+    let manifest = parse_manifest(MANIFEST).unwrap();
+    let api = ApiClient::new(&settings).unwrap();
+    let plan = api
+        .plan_yat(&yat, &manifest.yats[0].addresses)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        plan.changes.iter().map(|c| c.action).collect::<Vec<_>>(),
+        vec![PlanAction::Delete, PlanAction::Update, PlanAction::Insert]
+    );
+    // The social handle is not managed by the manifest
+    assert_eq!(
+        plan.edit_request(),
+        EditRequest {
+            insert: vec![
+                InsertData {
+                    data: "ADDR1|Savings||true".to_owned(),
+                    tag: "0x1012".to_owned(),
+                },
+                InsertData {
+                    data: "ADDR3".to_owned(),
+                    tag: "0x1012".to_owned(),
+                },
+            ],
+            delete: vec!["HASH2".to_owned(), "HASH1".to_owned()],
+        }
+    );
+
+    records_mock.assert();
+}
+
+#[tokio::test]
+async fn it_converges_after_apply() {
+    let server = MockYatServer::start("KEY").await.unwrap();
+    let settings = settings_for(server.base_url());
+    let yat = yat(&settings);
+    server.add_yat(yat.as_ref());
+    let manifest = parse_manifest(MANIFEST).unwrap();
+    let desired = &manifest.yats[0].addresses;
+    let api = ApiClient::new(&settings).unwrap();
+
+    let plan = api.plan_yat(&yat, desired).await.unwrap();
+    assert_eq!(plan.changes.len(), 2);
+    api.apply_edit(&yat, &plan.edit_request()).await.unwrap();
+
+    let plan = api.plan_yat(&yat, desired).await.unwrap();
+    assert!(plan.is_empty());
+    assert_eq!(server.records(yat.as_ref()).unwrap().len(), 2);
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use crate::{
+    api::{
+        client::ApiClient,
+        edit_request::{AddressSelector, NewAddress},
+        error::{ApiErrorCode, ClientError},
+    },
+    base::currency::Currency,
+    mock_server::{Failure, MockYatServer},
+    settings::{RetryPolicy, Settings},
+};
+
+use super::{settings_for, yat};
+
+async fn start() -> (MockYatServer, Settings) {
+    let server = MockYatServer::start("KEY").await.unwrap();
+    let settings = settings_for(server.base_url());
+    server.add_yat(yat(&settings).as_ref());
+    (server, settings)
+}
+
+fn new_address(address: &str, default: bool) -> NewAddress {
+    NewAddress {
+        currency: Currency::try_from("Cardano").unwrap(),
+        token: None,
+        address: address.to_owned(),
+        description: Some("Savings".to_owned()),
+        default,
+    }
+}
+
+#[tokio::test]
+async fn it_creates_looks_up_and_deletes_addresses() {
+    let (server, settings) = start().await;
+    let yat = yat(&settings);
+    let api = ApiClient::new(&settings).unwrap();
+
+    api.create_payment_addresses(&yat, vec![new_address("ADDR1", true)])
+        .await
+        .unwrap();
+    api.create_payment_addresses(&yat, vec![new_address("ADDR2", true)])
+        .await
+        .unwrap();
+
+    let addresses = api.fetch_payment_addresses(&yat).await.unwrap();
+    assert_eq!(addresses.len(), 2);
+    let default: Vec<_> = addresses.iter().filter(|a| a.default).collect();
+    assert_eq!(default.len(), 1);
+    assert_eq!(default[0].address, "ADDR2");
+
+    let selector = AddressSelector {
+        currency: Currency::try_from("Cardano").unwrap(),
+        address: Some("ADDR1".to_owned()),
+    };
+    api.delete_payment_address(&yat, selector).await.unwrap();
+
+    let addresses = api.fetch_payment_addresses(&yat).await.unwrap();
+    assert_eq!(addresses.len(), 1);
+    assert_eq!(addresses[0].address, "ADDR2");
+    assert_eq!(server.records(yat.as_ref()).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn it_requires_api_key() {
+    let (_server, settings) = start().await;
+    let settings = Settings {
+        yat_api_key: Some("WRONG".to_owned()),
+        ..settings
+    };
+    let api = ApiClient::new(&settings).unwrap();
+
+    let result = api.fetch_records(&yat(&settings)).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::ApiResponseError {
+            kind: ApiErrorCode::Unauthorized,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn it_fails_on_unknown_yat() {
+    let server = MockYatServer::start("KEY").await.unwrap();
+    let settings = settings_for(server.base_url());
+    let api = ApiClient::new(&settings).unwrap();
+
+    let result = api.fetch_records(&yat(&settings)).await;
+
+    assert!(matches!(result.unwrap_err(), ClientError::YatNotFound));
+}
+
+#[tokio::test]
+async fn it_injects_failures_in_order() {
+    let (server, settings) = start().await;
+    let yat = yat(&settings);
+    let api = ApiClient::new(&settings).unwrap();
+
+    server.fail_next(Failure::NotFound);
+    server.fail_next(Failure::ServerError);
+    server.fail_next(Failure::MalformedJson);
+
+    assert!(matches!(
+        api.fetch_records(&yat).await.unwrap_err(),
+        ClientError::YatNotFound
+    ));
+    assert!(matches!(
+        api.fetch_records(&yat).await.unwrap_err(),
+        ClientError::ApiResponseError {
+            kind: ApiErrorCode::ServerError,
+            ..
+        }
+    ));
+    assert!(matches!(
+        api.fetch_records(&yat).await.unwrap_err(),
+        ClientError::ReqwestError(_)
+    ));
+    assert!(api.fetch_records(&yat).await.is_ok());
+}
+
+#[tokio::test]
+async fn it_retries_after_rate_limit() {
+    let (server, settings) = start().await;
+    let settings = Settings {
+        retry: RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        },
+        ..settings
+    };
+    let api = ApiClient::new(&settings).unwrap();
+
+    server.fail_next(Failure::RateLimited { retry_after: None });
+
+    assert!(api.fetch_records(&yat(&settings)).await.is_ok());
+}
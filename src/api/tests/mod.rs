@@ -1,2 +1,47 @@
+use httpmock::MockServer;
+
+use crate::{
+    base::yat::Yat,
+    settings::{RetryPolicy, Settings},
+};
+
+mod auth;
+mod backup;
+mod cache;
 mod create_payment_address;
+mod delete_payment_address;
+mod fetch_many;
 mod fetch_payment_addresses;
+mod fixtures;
+mod http_client;
+mod list_owned_yats;
+mod manifest;
+mod mock_server;
+mod records;
+mod replace_payment_address;
+mod retry;
+mod search;
+mod set_default_address;
+
+fn build_settings(server: &MockServer) -> Settings {
+    settings_for(server.base_url())
+}
+
+fn settings_for(api_url: String) -> Settings {
+    Settings {
+        api_url,
+        yat_api_key: Some("KEY".to_owned()),
+        session_file: None,
+        required_yat_length: 2..=3,
+        retry: RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        },
+        http: Default::default(),
+        cache: Default::default(),
+    }
+}
+
+fn yat(settings: &Settings) -> Yat {
+    Yat::new(settings, "💪💪💪").unwrap()
+}
@@ -0,0 +1,155 @@
+use httpmock::prelude::*;
+use httpmock::Method::PATCH;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::json;
+
+use crate::{
+    api::{client::ApiClient, edit_request::NewRecord, error::ClientError},
+    base::{tag::Tag, yat::Yat},
+};
+
+use super::{build_settings, yat};
+
+fn path(yat: &Yat) -> String {
+    format!(
+        "/emoji_id/{}",
+        utf8_percent_encode(yat.as_ref(), NON_ALPHANUMERIC)
+    )
+}
+
+fn mock_records<'a>(server: &'a MockServer, yat: &Yat) -> httpmock::Mock<'a> {
+    server.mock(|when, then| {
+        when.method(GET).path(path(yat));
+        then.status(200).json_body(json!({
+          "result": [
+            { "tag": "0x1012", "data": "ADDR1", "hash": "HASH1" },
+            { "tag": "0x4001", "data": "https://old.example", "hash": "HASH2" },
+            { "tag": "0x4101", "data": "yat", "hash": "HASH3" },
+            { "tag": "0x4101", "data": "yat_fyi", "hash": "HASH4" },
+          ],
+          "status": true
+        }));
+    })
+}
+
+#[tokio::test]
+async fn it_lists_records_of_all_tags() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+
+    let api = ApiClient::new(&settings).unwrap();
+    let records = api.fetch_records(&yat).await.unwrap();
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].to_string(), "Cardano: ADDR1");
+    assert_eq!(
+        records[1].to_string(),
+        "Website redirect: https://old.example"
+    );
+
+    records_mock.assert();
+}
+
+#[tokio::test]
+async fn it_adds_records() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH)
+            .path(path(&yat))
+            .header("x-api-key", "KEY")
+            .json_body(json!({
+              "insert": [
+                { "data": "yat", "tag": "0x4102" },
+                { "data": "Hello", "tag": "0x9999" },
+              ]
+            }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let records = vec![
+        NewRecord::social("instagram", "@yat").unwrap(),
+        NewRecord::new(Tag::try_from("0x9999").unwrap(), "Hello").unwrap(),
+    ];
+    let result = api.add_records(&yat, records).await;
+
+    assert!(result.is_ok());
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_replaces_redirect() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH).path(path(&yat)).json_body(json!({
+          "insert": [{ "data": "https://new.example", "tag": "0x4001" }],
+          "delete": ["HASH2"]
+        }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let redirect = NewRecord::redirect("https://new.example").unwrap();
+    let result = api.set_record(&yat, redirect).await;
+
+    assert!(result.is_ok());
+    records_mock.assert();
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_removes_matching_record() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH)
+            .path(path(&yat))
+            .json_body(json!({ "delete": ["HASH4"] }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let twitter = Tag::try_from("twitter").unwrap();
+    let result = api.remove_records(&yat, &twitter, Some("yat_fyi")).await;
+
+    assert!(result.is_ok());
+    records_mock.assert();
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_fails_to_remove_ambiguous_records() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let twitter = Tag::try_from("twitter").unwrap();
+    let result = api.remove_records(&yat, &twitter, None).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::AmbiguousRecords(2)
+    ));
+    records_mock.assert();
+    edit_mock.assert_hits(0);
+}
@@ -0,0 +1,169 @@
+use httpmock::prelude::*;
+use httpmock::Method::PATCH;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::json;
+
+use crate::{
+    api::{
+        client::ApiClient,
+        edit_request::{AddressSelector, AddressUpdate},
+        error::ClientError,
+        payment_data::PaymentDataError,
+    },
+    base::{currency::Currency, yat::Yat},
+};
+
+use super::{build_settings, yat};
+
+fn path(yat: &Yat) -> String {
+    format!(
+        "/emoji_id/{}",
+        utf8_percent_encode(yat.as_ref(), NON_ALPHANUMERIC)
+    )
+}
+
+fn update_address(new_address: Option<&str>, description: Option<&str>) -> AddressUpdate {
+    AddressUpdate {
+        selector: AddressSelector {
+            currency: Currency::try_from("Cardano").unwrap(),
+            address: None,
+        },
+        new_address: new_address.map(|a| a.to_owned()),
+        description: description.map(|d| d.to_owned()),
+    }
+}
+
+fn mock_records<'a>(server: &'a MockServer, yat: &Yat) -> httpmock::Mock<'a> {
+    server.mock(|when, then| {
+        when.method(GET).path(path(yat));
+        then.status(200).json_body(json!({
+          "result": [
+            { "tag": "0x1012", "data": "ADDR1|Description1", "hash": "HASH1" },
+            { "tag": "0x1015", "data": "ADDR2", "hash": "HASH2" },
+          ],
+          "status": true
+        }));
+    })
+}
+
+#[tokio::test]
+async fn it_fails_if_no_record_matches() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = server.mock(|when, then| {
+        when.method(GET).path(path(&yat));
+        then.status(200)
+            .json_body(json!({ "result": [], "status": true }));
+    });
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .replace_payment_address(&yat, update_address(Some("NEW"), None))
+        .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::NoMatchingRecords
+    ));
+
+    records_mock.assert();
+    edit_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn it_replaces_address_in_single_request() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH)
+            .path(path(&yat))
+            .header("x-api-key", "KEY")
+            .json_body(json!({
+              "insert": [
+                { "data": "NEW|Description1", "tag": "0x1012" }
+              ],
+              "delete": ["HASH1"]
+            }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .replace_payment_address(&yat, update_address(Some("NEW"), None))
+        .await;
+
+    assert!(result.is_ok());
+
+    records_mock.assert();
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_changes_only_description() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH).path(path(&yat)).json_body(json!({
+          "insert": [
+            { "data": "ADDR1|Savings", "tag": "0x1012" }
+          ],
+          "delete": ["HASH1"]
+        }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .replace_payment_address(&yat, update_address(None, Some("Savings")))
+        .await;
+
+    assert!(result.is_ok());
+
+    records_mock.assert();
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_rejects_separator_in_new_values() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .replace_payment_address(&yat, update_address(Some("ADDR3|SIG"), None))
+        .await;
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::PaymentDataError(PaymentDataError::SeparatorInAddress)
+    ));
+
+    let result = api
+        .replace_payment_address(&yat, update_address(None, Some("x||true")))
+        .await;
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::PaymentDataError(PaymentDataError::SeparatorInDescription)
+    ));
+
+    records_mock.assert_hits(2);
+    edit_mock.assert_hits(0);
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use httpmock::prelude::*;
+use httpmock::Method::PATCH;
+
+use crate::{
+    api::{client::ApiClient, edit_request::NewAddress, error::ClientError},
+    base::currency::Currency,
+    settings::{RetryPolicy, Settings},
+};
+
+use super::{build_settings, yat};
+
+fn retrying_settings(server: &MockServer, retry_patch: bool) -> Settings {
+    Settings {
+        retry: RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+            jitter: true,
+            retry_patch,
+        },
+        ..build_settings(server)
+    }
+}
+
+fn new_address() -> NewAddress {
+    NewAddress {
+        currency: Currency::try_from("Cardano").unwrap(),
+        token: None,
+        address: "ADDR".to_owned(),
+        description: None,
+        default: false,
+    }
+}
+
+#[tokio::test]
+async fn it_retries_get_on_server_errors() {
+    let server = MockServer::start();
+    let settings = retrying_settings(&server, false);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(503).header("Retry-After", "0");
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(matches!(result.unwrap_err(), ClientError::ReqwestError(_)));
+
+    mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn it_does_not_wait_for_long_retry_after() {
+    let server = MockServer::start();
+    let settings = retrying_settings(&server, false);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(429).header("Retry-After", "60");
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(matches!(result.unwrap_err(), ClientError::ReqwestError(_)));
+
+    mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn it_does_not_retry_client_errors() {
+    let server = MockServer::start();
+    let settings = retrying_settings(&server, false);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET);
+        then.status(404);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.fetch_payment_addresses(&yat).await;
+
+    assert!(matches!(result.unwrap_err(), ClientError::YatNotFound));
+
+    mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn it_does_not_retry_patch_by_default() {
+    let server = MockServer::start();
+    let settings = retrying_settings(&server, false);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(500);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address()])
+        .await;
+
+    assert!(result.is_err());
+
+    mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn it_retries_patch_when_asked() {
+    let server = MockServer::start();
+    let settings = retrying_settings(&server, true);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(429);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(&yat, vec![new_address()])
+        .await;
+
+    assert!(result.is_err());
+
+    mock.assert_hits(3);
+}
@@ -0,0 +1,93 @@
+use httpmock::prelude::*;
+use serde_json::json;
+
+use crate::{
+    api::{client::ApiClient, error::ClientError, search::Availability},
+    base::yat::Yat,
+    settings::Settings,
+};
+
+use super::{build_settings, yat};
+
+#[tokio::test]
+async fn it_returns_availability_with_alternates() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/emoji_id/search")
+            .query_param("emoji_id", yat.as_ref());
+        then.status(200).json_body(json!({
+          "result": { "emoji_id": yat.as_ref(), "availability": "Taken" },
+          "alternates": [
+            { "emoji_id": "🐱🐶", "availability": "Available", "price": 2500 },
+            { "emoji_id": "🐶🐱", "availability": "ComingSoon" },
+          ],
+          "status": true
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let search = api.search(&yat).await.unwrap();
+
+    assert_eq!(search.result.availability, Availability::Taken);
+    assert_eq!(search.result.price, None);
+    assert_eq!(search.alternates.len(), 2);
+    assert_eq!(search.alternates[0].price, Some(2500));
+    assert_eq!(search.alternates[1].availability, Availability::Unknown);
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_fails_on_api_error() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/emoji_id/search");
+        then.status(422).json_body(json!({
+          "error": { "code": "ValidationFailed", "reason": "Invalid emoji" }
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api.search(&yat).await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::ApiResponseError { .. }
+    ));
+    mock.assert();
+}
+
+#[tokio::test]
+async fn it_searches_without_credentials() {
+    let server = MockServer::start();
+    let settings = Settings {
+        yat_api_key: None,
+        session_file: None,
+        ..build_settings(&server)
+    };
+    let yat = Yat::partial("💪").unwrap();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/emoji_id/search")
+            .query_param("emoji_id", "💪");
+        then.status(200).json_body(json!({
+          "result": { "emoji_id": "💪", "availability": "Taken" },
+          "alternates": [],
+          "status": true
+        }));
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let search = api.search(&yat).await.unwrap();
+
+    assert_eq!(search.result.availability, Availability::Taken);
+    mock.assert();
+}
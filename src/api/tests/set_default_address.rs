@@ -0,0 +1,145 @@
+use httpmock::prelude::*;
+use httpmock::Method::PATCH;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::json;
+
+use crate::{
+    api::{
+        client::ApiClient,
+        edit_request::{AddressSelector, NewAddress},
+        error::ClientError,
+    },
+    base::{currency::Currency, yat::Yat},
+};
+
+use super::{build_settings, yat};
+
+fn path(yat: &Yat) -> String {
+    format!(
+        "/emoji_id/{}",
+        utf8_percent_encode(yat.as_ref(), NON_ALPHANUMERIC)
+    )
+}
+
+fn cardano() -> Currency {
+    Currency::try_from("Cardano").unwrap()
+}
+
+fn mock_records<'a>(server: &'a MockServer, yat: &Yat) -> httpmock::Mock<'a> {
+    server.mock(|when, then| {
+        when.method(GET).path(path(yat));
+        then.status(200).json_body(json!({
+          "result": [
+            { "tag": "0x1012", "data": "ADDR1|Savings||true", "hash": "HASH1" },
+            { "tag": "0x1012", "data": "ADDR2", "hash": "HASH2" },
+            { "tag": "0x1015", "data": "ADDR3|||true", "hash": "HASH3" },
+          ],
+          "status": true
+        }));
+    })
+}
+
+#[tokio::test]
+async fn it_moves_default_within_currency() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH).path(path(&yat)).json_body(json!({
+          "insert": [
+            { "data": "ADDR1|Savings", "tag": "0x1012" },
+            { "data": "ADDR2|||true", "tag": "0x1012" }
+          ],
+          "delete": ["HASH1", "HASH2"]
+        }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .set_default_address(
+            &yat,
+            AddressSelector {
+                currency: cardano(),
+                address: Some("ADDR2".to_owned()),
+            },
+        )
+        .await;
+
+    assert!(result.is_ok());
+
+    records_mock.assert();
+    edit_mock.assert();
+}
+
+#[tokio::test]
+async fn it_requires_address_if_currency_has_several() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH);
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .set_default_address(
+            &yat,
+            AddressSelector {
+                currency: cardano(),
+                address: None,
+            },
+        )
+        .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        ClientError::AmbiguousRecords(2)
+    ));
+
+    records_mock.assert();
+    edit_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn it_creates_default_address_replacing_current_default() {
+    let server = MockServer::start();
+    let settings = build_settings(&server);
+    let yat = yat(&settings);
+
+    let records_mock = mock_records(&server, &yat);
+    let edit_mock = server.mock(|when, then| {
+        when.method(PATCH).path(path(&yat)).json_body(json!({
+          "insert": [
+            { "data": "ADDR1|Savings", "tag": "0x1012" },
+            { "data": "NEW|||true", "tag": "0x1012" }
+          ],
+          "delete": ["HASH1"]
+        }));
+        then.status(200);
+    });
+
+    let api = ApiClient::new(&settings).unwrap();
+    let result = api
+        .create_payment_addresses(
+            &yat,
+            vec![NewAddress {
+                currency: cardano(),
+                token: None,
+                address: "NEW".to_owned(),
+                description: None,
+                default: true,
+            }],
+        )
+        .await;
+
+    assert!(result.is_ok());
+
+    records_mock.assert();
+    edit_mock.assert();
+}
@@ -0,0 +1,212 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::payment_address::FriendlyPaymentAddress;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed, // Same address with another description, default flag or signature
+}
+
+/// A difference between two lookups of the same yat
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressChange {
+    pub kind: ChangeKind,
+    pub old: Option<FriendlyPaymentAddress>,
+    pub new: Option<FriendlyPaymentAddress>,
+}
+
+impl AddressChange {
+    /// The address after the change, or the removed one
+    pub fn address(&self) -> &FriendlyPaymentAddress {
+        self.new
+            .as_ref()
+            .or(self.old.as_ref())
+            .expect("A change has at least one side")
+    }
+
+    /// A single NDJSON line
+    pub fn to_json(&self, yat: &str) -> Value {
+        let address = self.address();
+        json!({
+            "yat": yat,
+            "change": self.kind,
+            "currency": address.currency.get_name(),
+            "currency_code": address.currency.get_hex_code(),
+            "address": address.address,
+            "old": self.old.as_ref().map(address_json),
+            "new": self.new.as_ref().map(address_json),
+        })
+    }
+}
+
+impl Display for AddressChange {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let address = self.address();
+        let kind = match self.kind {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Changed => "Changed",
+        };
+        write!(
+            fmt,
+            "{} {} address {}",
+            kind,
+            address.currency.get_name(),
+            address.address
+        )?;
+        if let (Some(old), Some(new)) = (&self.old, &self.new) {
+            if old.description != new.description {
+                write!(
+                    fmt,
+                    ", description: {:?} -> {:?}",
+                    old.description.as_deref().unwrap_or_default(),
+                    new.description.as_deref().unwrap_or_default()
+                )?;
+            }
+            if old.default != new.default {
+                write!(fmt, ", default: {} -> {}", old.default, new.default)?;
+            }
+            if old.signature != new.signature {
+                write!(fmt, ", signature changed")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn address_json(address: &FriendlyPaymentAddress) -> Value {
+    json!({
+        "description": address.description,
+        "default": address.default,
+        "signature": address.signature,
+        "token": address.short_name,
+        "settlement_network": address.settlement_network,
+    })
+}
+
+// Addresses are the same one, if they share currency, token and address
+fn same_address(a: &FriendlyPaymentAddress, b: &FriendlyPaymentAddress) -> bool {
+    a.currency == b.currency
+        && a.address == b.address
+        && a.short_name == b.short_name
+        && a.settlement_network == b.settlement_network
+}
+
+fn same_details(a: &FriendlyPaymentAddress, b: &FriendlyPaymentAddress) -> bool {
+    a.description == b.description && a.default == b.default && a.signature == b.signature
+}
+
+/// Changes between the previous and the current lookup: removals first, then changes and
+/// additions in the order of the current lookup
+pub fn diff_addresses(
+    old: &[FriendlyPaymentAddress],
+    new: &[FriendlyPaymentAddress],
+) -> Vec<AddressChange> {
+    let mut changes: Vec<AddressChange> = old
+        .iter()
+        .filter(|o| !new.iter().any(|n| same_address(o, n)))
+        .map(|o| AddressChange {
+            kind: ChangeKind::Removed,
+            old: Some(o.clone()),
+            new: None,
+        })
+        .collect();
+    for n in new {
+        match old.iter().find(|o| same_address(o, n)) {
+            Some(o) if same_details(o, n) => {}
+            Some(o) => changes.push(AddressChange {
+                kind: ChangeKind::Changed,
+                old: Some(o.clone()),
+                new: Some(n.clone()),
+            }),
+            None => changes.push(AddressChange {
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some(n.clone()),
+            }),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api::payment_address::SignatureStatus, base::currency::Currency};
+
+    use super::*;
+
+    fn address(address: &str, description: Option<&str>, default: bool) -> FriendlyPaymentAddress {
+        FriendlyPaymentAddress {
+            currency: Currency::try_from("Cardano").unwrap(),
+            address: address.to_owned(),
+            description: description.map(|d| d.to_owned()),
+            default,
+            signature: None,
+            signature_status: SignatureStatus::Unsigned,
+            short_name: None,
+            long_name: None,
+            settlement_network: None,
+        }
+    }
+
+    #[test]
+    fn it_finds_no_changes_in_same_lookup() {
+        let addresses = vec![address("ADDR1", None, true), address("ADDR2", None, false)];
+        assert!(diff_addresses(&addresses, &addresses).is_empty());
+    }
+
+    #[test]
+    fn it_finds_added_removed_and_changed_addresses() {
+        let old = vec![address("ADDR1", None, true), address("ADDR2", None, false)];
+        let new = vec![
+            address("ADDR2", Some("Savings"), true),
+            address("ADDR3", None, false),
+        ];
+        let changes = diff_addresses(&old, &new);
+
+        assert_eq!(
+            changes.iter().map(|c| c.kind).collect::<Vec<_>>(),
+            vec![ChangeKind::Removed, ChangeKind::Changed, ChangeKind::Added]
+        );
+        assert_eq!(changes[0].to_string(), "Removed Cardano address ADDR1");
+        assert_eq!(
+            changes[1].to_string(),
+            "Changed Cardano address ADDR2, description: \"\" -> \"Savings\", \
+             default: false -> true"
+        );
+        assert_eq!(changes[2].to_string(), "Added Cardano address ADDR3");
+    }
+
+    #[test]
+    fn it_serializes_change_as_json() {
+        let change = AddressChange {
+            kind: ChangeKind::Added,
+            old: None,
+            new: Some(address("ADDR", Some("Savings"), true)),
+        };
+        assert_eq!(
+            change.to_json("🐱🐶"),
+            json!({
+                "yat": "🐱🐶",
+                "change": "added",
+                "currency": "Cardano",
+                "currency_code": "0x1012",
+                "address": "ADDR",
+                "old": null,
+                "new": {
+                    "description": "Savings",
+                    "default": true,
+                    "signature": null,
+                    "token": null,
+                    "settlement_network": null,
+                },
+            })
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
+use super::token::TOKEN_CATEGORY;
+
 static CODE_PREFIX: &str = "0x";
 
-const CURRENCIES: [(u16, &str); 57] = [
+const CURRENCIES: [(u16, &str); 58] = [
     (0x1001, "Monero standard address"),
     (0x1002, "Monero sub address"),
     (0x1003, "Bitcoin address"),
@@ -60,6 +62,7 @@ const CURRENCIES: [(u16, &str); 57] = [
     (0x106C, "Harmony"),
     (0x106D, "Celo"),
     (0x106F, "Arweave"),
+    (0x6300, "CryptoToken"),
 ];
 
 lazy_static! {
@@ -99,6 +102,11 @@ impl Currency {
     pub fn get_name(&self) -> &'static str {
         self.name
     }
+
+    /// Token addresses share a single category
+    pub fn is_token(&self) -> bool {
+        self.code == TOKEN_CATEGORY
+    }
 }
 
 impl From<(u16, &'static str)> for Currency {
@@ -1,3 +1,5 @@
 pub mod currency;
 pub mod emoji;
+pub mod tag;
+pub mod token;
 pub mod yat;
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use super::currency::Currency;
+
+static CODE_PREFIX: &str = "0x";
+
+/// How the data of a tag is validated
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TagKind {
+    Url,    // http(s) URL
+    Handle, // Social network user name, stored without `@`
+    Text,   // Anything, except `|`
+}
+
+const TAGS: [(u16, &str, &str, TagKind); 10] = [
+    (0x4001, "redirect", "Website redirect", TagKind::Url),
+    (0x4002, "website", "Website", TagKind::Url),
+    (0x4003, "email", "Email", TagKind::Text),
+    (0x4101, "twitter", "Twitter", TagKind::Handle),
+    (0x4102, "instagram", "Instagram", TagKind::Handle),
+    (0x4103, "youtube", "YouTube", TagKind::Handle),
+    (0x4104, "tiktok", "TikTok", TagKind::Handle),
+    (0x4105, "discord", "Discord", TagKind::Handle),
+    (0x4106, "telegram", "Telegram", TagKind::Handle),
+    (0x4107, "github", "GitHub", TagKind::Handle),
+];
+
+lazy_static! {
+    static ref TAG_BY_CODE: HashMap<u16, Tag> =
+        HashMap::from_iter(TAGS.into_iter().map(|tag| (tag.0, tag.into())));
+}
+
+lazy_static! {
+    static ref TAG_BY_NAME: HashMap<&'static str, Tag> =
+        HashMap::from_iter(TAGS.into_iter().map(|tag| (tag.1, tag.into())));
+}
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum TagError {
+    #[error("Tag `{0}` was not found")]
+    MissingTag(String),
+    #[error("Invalid tag code `{0}`")]
+    InvalidTagCode(#[from] std::num::ParseIntError),
+    #[error("Invalid data for tag `{tag}`: {reason}")]
+    InvalidData { tag: String, reason: String },
+}
+
+/// Type of a yat record. Tags missing from the registry are kept by their code only
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Tag {
+    code: u16,
+    name: Option<&'static str>,
+    description: Option<&'static str>,
+    kind: TagKind,
+}
+
+impl Tag {
+    pub fn get_hex_code(&self) -> String {
+        format!("{:#x}", self.code)
+    }
+
+    pub fn get_code(&self) -> u16 {
+        self.code
+    }
+
+    /// Name in the registry, e.g. `redirect`
+    pub fn get_name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Validates and normalizes record data
+    pub fn validate(&self, data: &str) -> Result<String, TagError> {
+        let data = data.trim();
+        let invalid = |reason: &str| TagError::InvalidData {
+            tag: self.to_string(),
+            reason: reason.to_owned(),
+        };
+        if data.is_empty() {
+            return Err(invalid("data is empty"));
+        }
+        if data.contains('|') {
+            return Err(invalid("data must not contain `|`"));
+        }
+        match self.kind {
+            TagKind::Url if !data.starts_with("https://") && !data.starts_with("http://") => {
+                Err(invalid("URL must start with http:// or https://"))
+            }
+            TagKind::Handle => Ok(data.trim_start_matches('@').to_owned()),
+            _ => Ok(data.to_owned()),
+        }
+    }
+}
+
+impl From<(u16, &'static str, &'static str, TagKind)> for Tag {
+    fn from(tag: (u16, &'static str, &'static str, TagKind)) -> Self {
+        Self {
+            code: tag.0,
+            name: Some(tag.1),
+            description: Some(tag.2),
+            kind: tag.3,
+        }
+    }
+}
+
+impl From<u16> for Tag {
+    fn from(code: u16) -> Self {
+        TAG_BY_CODE.get(&code).cloned().unwrap_or(Self {
+            code,
+            name: None,
+            description: None,
+            kind: TagKind::Text,
+        })
+    }
+}
+
+impl TryFrom<&str> for Tag {
+    type Error = TagError;
+
+    /// A hex code (any, including payment tags) or a name from the registry
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        if name.starts_with(CODE_PREFIX) {
+            let code = u16::from_str_radix(name.trim_start_matches(CODE_PREFIX), 16)?;
+            return Ok(Tag::from(code));
+        }
+        TAG_BY_NAME
+            .get(name.to_lowercase().as_str())
+            .cloned()
+            .ok_or_else(|| TagError::MissingTag(name.to_owned()))
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match (self.description, Currency::try_from(self.code)) {
+            (Some(description), _) => write!(fmt, "{}", description),
+            (None, Ok(currency)) => write!(fmt, "{}", currency.get_name()),
+            (None, Err(_)) => write!(fmt, "Unknown ({})", self.get_hex_code()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_tag_by_name_or_code() {
+        let redirect = Tag::try_from("Redirect").unwrap();
+        assert_eq!(redirect.get_hex_code(), "0x4001");
+        assert_eq!(Tag::try_from("0x4001"), Ok(redirect));
+        assert_eq!(
+            Tag::try_from("homepage"),
+            Err(TagError::MissingTag("homepage".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_keeps_unknown_codes() {
+        let tag = Tag::try_from("0x9999").unwrap();
+        assert_eq!(tag.get_name(), None);
+        assert_eq!(tag.to_string(), "Unknown (0x9999)");
+        assert_eq!(Tag::from(0x1012).to_string(), "Cardano");
+    }
+
+    #[test]
+    fn it_validates_data_by_kind() {
+        let redirect = Tag::try_from("redirect").unwrap();
+        assert_eq!(
+            redirect.validate(" https://example.com "),
+            Ok("https://example.com".to_owned())
+        );
+        assert!(redirect.validate("example.com").is_err());
+
+        let twitter = Tag::try_from("twitter").unwrap();
+        assert_eq!(twitter.validate("@yat"), Ok("yat".to_owned()));
+        assert!(twitter.validate("a|b").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::{Display, Formatter};
+
+use super::currency::Currency;
+
+/// Category code of all token addresses. The token itself is a part of the record data
+pub const TOKEN_CATEGORY: u16 = 0x6300;
+
+// Long names of well known tokens. Other symbols are accepted as well
+const TOKENS: [(&str, &str); 8] = [
+    ("USDC", "USD Coin"),
+    ("USDT", "Tether USD"),
+    ("DAI", "Dai Stablecoin"),
+    ("BUSD", "Binance USD"),
+    ("WBTC", "Wrapped Bitcoin"),
+    ("LINK", "Chainlink"),
+    ("UNI", "Uniswap"),
+    ("SHIB", "Shiba Inu"),
+];
+
+// Spelling of well known chains. Other networks are accepted as well
+const NETWORKS: [&str; 6] = [
+    "Ethereum",
+    "Polygon",
+    "BNB Smart Chain",
+    "Avalanche",
+    "Solana",
+    "TRON",
+];
+
+const MAX_SYMBOL_LENGTH: usize = 16;
+const MAX_NETWORK_LENGTH: usize = 32;
+const MAX_CONTRACT_LENGTH: usize = 128;
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum TokenError {
+    #[error("Invalid token symbol `{0}`")]
+    InvalidSymbol(String),
+    #[error("Invalid settlement network `{0}`")]
+    InvalidNetwork(String),
+    #[error("Invalid token contract `{0}`")]
+    InvalidContract(String),
+}
+
+/// A token, such as an ERC20 one, settled on a chain
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Token {
+    pub symbol: String,           // Short name, e.g. USDC
+    pub name: String,             // Long name, e.g. USD Coin. The symbol, if it is not known
+    pub network: String,          // Settlement network, e.g. Ethereum
+    pub contract: Option<String>, // Contract address on the network
+}
+
+impl Token {
+    /// Validates a token. Well known symbols and networks are matched ignoring case and take
+    /// their usual spelling
+    pub fn new(symbol: &str, network: &str, contract: Option<&str>) -> Result<Self, TokenError> {
+        let symbol = symbol.trim();
+        let is_symbol_char = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+        if symbol.is_empty()
+            || symbol.len() > MAX_SYMBOL_LENGTH
+            || !symbol.chars().all(is_symbol_char)
+        {
+            return Err(TokenError::InvalidSymbol(symbol.to_owned()));
+        }
+        let network = network.trim();
+        let is_network_char = |c: char| c.is_alphanumeric() || c == ' ' || c == '-' || c == '.';
+        if network.is_empty()
+            || network.len() > MAX_NETWORK_LENGTH
+            || !network.chars().all(is_network_char)
+        {
+            return Err(TokenError::InvalidNetwork(network.to_owned()));
+        }
+        let contract = contract.map(str::trim).filter(|c| !c.is_empty());
+        if let Some(contract) = contract {
+            if contract.len() > MAX_CONTRACT_LENGTH
+                || !contract.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                return Err(TokenError::InvalidContract(contract.to_owned()));
+            }
+        }
+
+        let (symbol, name) = TOKENS
+            .into_iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+            .unwrap_or((symbol, symbol));
+        let network = NETWORKS
+            .into_iter()
+            .find(|n| n.eq_ignore_ascii_case(network))
+            .unwrap_or(network);
+        Ok(Self {
+            symbol: symbol.to_owned(),
+            name: name.to_owned(),
+            network: network.to_owned(),
+            contract: contract.map(str::to_owned),
+        })
+    }
+
+    /// Currency of the token records
+    pub fn currency() -> Currency {
+        Currency::try_from(TOKEN_CATEGORY).expect("Token category is a known currency")
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{} on {}", self.symbol, self.network)?;
+        if let Some(contract) = &self.contract {
+            write!(fmt, " ({})", contract)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_known_token_and_network_ignoring_case() {
+        assert_eq!(
+            Token::new("usdc", "ethereum", None),
+            Ok(Token {
+                symbol: "USDC".to_owned(),
+                name: "USD Coin".to_owned(),
+                network: "Ethereum".to_owned(),
+                contract: None,
+            })
+        );
+    }
+
+    #[test]
+    fn it_accepts_any_well_formed_token() {
+        let token = Token::new("stETH", "Arbitrum One", Some(" 0xAbC123 ")).unwrap();
+        assert_eq!(
+            token,
+            Token {
+                symbol: "stETH".to_owned(),
+                name: "stETH".to_owned(),
+                network: "Arbitrum One".to_owned(),
+                contract: Some("0xAbC123".to_owned()),
+            }
+        );
+        assert_eq!(token.to_string(), "stETH on Arbitrum One (0xAbC123)");
+    }
+
+    #[test]
+    fn it_fails_on_malformed_token() {
+        assert_eq!(
+            Token::new("US|DC", "Ethereum", None),
+            Err(TokenError::InvalidSymbol("US|DC".to_owned()))
+        );
+        assert_eq!(
+            Token::new("USDT", "", None),
+            Err(TokenError::InvalidNetwork("".to_owned()))
+        );
+        assert_eq!(
+            Token::new("USDT", "TRON", Some("0x 12")),
+            Err(TokenError::InvalidContract("0x 12".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_uses_token_category() {
+        assert_eq!(Token::currency().get_hex_code(), "0x6300");
+    }
+}
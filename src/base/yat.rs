@@ -28,6 +28,20 @@ impl Yat {
             signature: signature.to_owned(),
         })
     }
+
+    /// Yat of any length, such as a search candidate. Only the emojis are checked
+    pub fn partial(signature: &str) -> Result<Self, YatError> {
+        let s: Vec<char> = drop_combining_marks(signature.chars()).collect();
+        if s.is_empty() {
+            return Err(YatError::InvalidLength);
+        }
+        if !s.into_iter().all(is_valid_emoji) {
+            return Err(YatError::InvalidEmojis);
+        }
+        Ok(Self {
+            signature: signature.to_owned(),
+        })
+    }
 }
 
 impl AsRef<str> for Yat {
@@ -43,8 +57,12 @@ mod tests {
     fn settings() -> Settings {
         Settings {
             api_url: "https://".to_owned(),
-            yat_api_key: "KEY".to_owned(),
+            yat_api_key: Some("KEY".to_owned()),
+            session_file: None,
             required_yat_length: 2..=3,
+            retry: Default::default(),
+            http: Default::default(),
+            cache: Default::default(),
         }
     }
 
@@ -66,6 +84,14 @@ mod tests {
         assert_eq!(Yat::new(&settings(), "XX"), Err(YatError::InvalidEmojis));
     }
 
+    #[test]
+    fn it_creates_partial_yat_of_any_length() {
+        assert_eq!(Yat::partial("💪").map(|y| y.signature), Ok("💪".to_owned()));
+        assert!(Yat::partial("💪💪💪💪💪💪").is_ok());
+        assert_eq!(Yat::partial(""), Err(YatError::InvalidLength));
+        assert_eq!(Yat::partial("XX"), Err(YatError::InvalidEmojis));
+    }
+
     #[test]
     fn it_creates_yat_if_length_is_adequate() {
         let yat = Yat::new(&settings(), "💪💪");
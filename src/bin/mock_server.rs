@@ -0,0 +1,41 @@
+use std::net::SocketAddr;
+
+use clap::Parser;
+use yat::mock_server::MockYatServer;
+
+/// In-memory stand-in for the Yat API
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, short, default_value = "127.0.0.1:3030")]
+    listen: SocketAddr,
+    /// API key, which the clients must send
+    #[arg(long, short = 'k')]
+    api_key: String,
+    /// Yats, which exist from the start
+    #[arg(long, short)]
+    yat: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+    let args = Args::parse();
+
+    let server = MockYatServer::bind(args.listen, &args.api_key)
+        .await
+        .unwrap_or_else(|err| {
+            log::error!("{}", err);
+            std::process::exit(1);
+        });
+    for yat in &args.yat {
+        server.add_yat(yat);
+    }
+    log::info!("Mock Yat API is running on {}", server.base_url());
+
+    // Runs until the process is stopped
+    std::future::pending::<()>().await;
+}
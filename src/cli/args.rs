@@ -0,0 +1,343 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::{ArgGroup, Args, Parser, Subcommand};
+
+use yat::{
+    api::{
+        address_file::read_address_file,
+        edit_request::{AddressSelector, AddressUpdate, NewAddress},
+        error::ClientError,
+        payment_address::PublicKey,
+    },
+    base::{currency::Currency, tag::Tag, token::Token},
+};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+pub struct Cli {
+    /// Yat (an emoji string)
+    #[arg(long, short)]
+    pub yat: Option<String>,
+    /// Use only cached lookups, never call the API
+    #[arg(long)]
+    pub offline: bool,
+    /// Show the edit request of a mutating command without sending it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Apply changes without asking for confirmation
+    #[arg(long, global = true)]
+    pub yes: bool,
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Looks up the payment addresses
+    #[command(name = "lookup")]
+    LookupAddress(LookupAddress),
+    /// Create new payment addresses
+    #[command(name = "create")]
+    CreateAddress(CreateAddress),
+    /// Delete a payment address
+    #[command(name = "delete")]
+    DeleteAddress(DeleteAddress),
+    /// Replace an existing payment address or its description
+    #[command(name = "update")]
+    UpdateAddress(UpdateAddress),
+    /// Make a payment address the default one of its currency
+    #[command(name = "set-default")]
+    SetDefault(SetDefault),
+    /// List, add or remove records of any tag, e.g. a website redirect or social handles
+    #[command(name = "records")]
+    Records(Records),
+    /// Check availability and price of yats before buying them
+    #[command(name = "search")]
+    Search(Search),
+    /// Poll the payment addresses and report changes
+    #[command(name = "watch")]
+    Watch(Watch),
+    /// Show the changes, which would make the payment addresses match a manifest
+    #[command(name = "plan")]
+    Plan(Manifest),
+    /// Change the payment addresses to match a manifest
+    #[command(name = "apply")]
+    Apply(Manifest),
+    /// Write all records of a yat as a JSON backup to the standard output
+    #[command(name = "export")]
+    Export,
+    /// Bring the records of a yat back to a backup written by `export`
+    #[command(name = "restore")]
+    Restore(Restore),
+    /// List yats owned by the account
+    #[command(name = "list")]
+    ListOwned,
+    /// Log in with email and password instead of using an API key
+    #[command(name = "login")]
+    Login(Login),
+}
+
+#[derive(Debug, Args)]
+pub struct LookupAddress {
+    // Currency
+    #[arg(long, short, value_parser = validate_currency)]
+    pub currency: Option<Currency>,
+    // Show only the default address of each category
+    #[arg(long)]
+    pub default_only: bool,
+    // Fail if any address has an unknown category
+    #[arg(long)]
+    pub strict: bool,
+    // Hex encoded Ed25519 key, which signed the addresses. Signatures are verified locally
+    #[arg(long, value_parser = validate_public_key)]
+    pub public_key: Option<PublicKey>,
+    // Fail the lookup, if an address has no signature verified by the public key
+    #[arg(long, requires = "public_key")]
+    pub require_signed: bool,
+    // File with one yat per line, `-` reads them from the standard input. Replaces `--yat`
+    #[arg(long)]
+    pub yats_file: Option<PathBuf>,
+    // Look up every yat owned by the account. Replaces `--yat`
+    #[arg(long, conflicts_with = "yats_file")]
+    pub all_owned: bool,
+    // Maximum number of lookups running at once with `--yats-file` or `--all-owned`
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: u16,
+}
+
+#[derive(Debug, Args)]
+pub struct CreateAddress {
+    // Currency
+    #[arg(
+        long,
+        short,
+        value_parser = validate_payment_currency,
+        required_unless_present_any = ["from_file", "token"],
+        conflicts_with = "token"
+    )]
+    pub currency: Option<Currency>,
+    // Token symbol, e.g. USDC. Used instead of a currency
+    #[arg(long, short, requires = "network")]
+    pub token: Option<String>,
+    // Settlement network of the token, e.g. Ethereum
+    #[arg(long, short, requires = "token")]
+    pub network: Option<String>,
+    // Contract address of the token on its network
+    #[arg(long, requires = "token")]
+    pub contract: Option<String>,
+    // Wallet address
+    #[arg(long, short, required_unless_present = "from_file")]
+    pub address: Option<String>,
+    // Name of the wallet
+    #[arg(long, short)]
+    pub description: Option<String>,
+    // Make it the default address of the currency
+    #[arg(long)]
+    pub default: bool,
+    // CSV, JSON or YAML file with `currency` (or `token`, `network` and `contract`), `address`,
+    // `description` and `default` fields
+    #[arg(
+        long,
+        short,
+        conflicts_with_all = ["currency", "token", "contract", "address", "description", "default"]
+    )]
+    pub from_file: Option<PathBuf>,
+}
+
+impl CreateAddress {
+    /// Addresses to create, given either by arguments or by a file
+    pub fn into_addresses(self) -> Result<Vec<NewAddress>, ClientError> {
+        let (currency, token) = match (self.from_file, self.currency, self.token, self.network) {
+            (Some(path), _, _, _) => return Ok(read_address_file(&path)?),
+            (None, Some(currency), _, _) => (currency, None),
+            (None, None, Some(token), Some(network)) => {
+                let token = Token::new(&token, &network, self.contract.as_deref())?;
+                (Token::currency(), Some(token))
+            }
+            _ => unreachable!("Arguments are enforced by clap"),
+        };
+        Ok(vec![NewAddress {
+            currency,
+            token,
+            address: self.address.expect("Address is enforced by clap"),
+            description: self.description,
+            default: self.default,
+        }])
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteAddress {
+    // Currency
+    #[arg(long, short, value_parser = validate_currency)]
+    pub currency: Currency,
+    // Wallet address. Required, if the currency has several addresses
+    #[arg(long, short)]
+    pub address: Option<String>,
+}
+
+impl From<DeleteAddress> for AddressSelector {
+    fn from(delete: DeleteAddress) -> Self {
+        Self {
+            currency: delete.currency,
+            address: delete.address,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(group(
+    ArgGroup::new("change")
+        .required(true)
+        .multiple(true)
+        .args(["new_address", "description"]),
+))]
+pub struct UpdateAddress {
+    // Currency
+    #[arg(long, short, value_parser = validate_currency)]
+    pub currency: Currency,
+    // Current wallet address. Required, if the currency has several addresses
+    #[arg(long, short)]
+    pub address: Option<String>,
+    // New wallet address. The current one is kept, if not given
+    #[arg(long, short)]
+    pub new_address: Option<String>,
+    // New name of the wallet. The current one is kept, if not given
+    #[arg(long, short)]
+    pub description: Option<String>,
+}
+
+impl From<UpdateAddress> for AddressUpdate {
+    fn from(update: UpdateAddress) -> Self {
+        Self {
+            selector: AddressSelector {
+                currency: update.currency,
+                address: update.address,
+            },
+            new_address: update.new_address,
+            description: update.description,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct SetDefault {
+    // Currency
+    #[arg(long, short, value_parser = validate_currency)]
+    pub currency: Currency,
+    // Wallet address. Required, if the currency has several addresses
+    #[arg(long, short)]
+    pub address: Option<String>,
+}
+
+impl From<SetDefault> for AddressSelector {
+    fn from(set_default: SetDefault) -> Self {
+        Self {
+            currency: set_default.currency,
+            address: set_default.address,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct Records {
+    #[command(subcommand)]
+    pub action: RecordsAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RecordsAction {
+    /// List all records of the yat
+    List {
+        // Tag name (e.g. redirect, twitter) or hex code (e.g. 0x4001)
+        #[arg(long, short, value_parser = validate_tag)]
+        tag: Option<Tag>,
+    },
+    /// Add a record
+    Add {
+        // Tag name (e.g. redirect, twitter) or hex code (e.g. 0x4001)
+        #[arg(long, short, value_parser = validate_tag)]
+        tag: Tag,
+        // Record data, e.g. a URL or a user name
+        #[arg(long, short)]
+        data: String,
+        // Remove the other records of the tag
+        #[arg(long)]
+        replace: bool,
+    },
+    /// Remove records of a tag
+    Remove {
+        // Tag name (e.g. redirect, twitter) or hex code (e.g. 0x4001)
+        #[arg(long, short, value_parser = validate_tag)]
+        tag: Tag,
+        // Record data. Required, if the tag has several records
+        #[arg(long, short)]
+        data: Option<String>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct Search {
+    // Candidate yats
+    #[arg(required = true)]
+    pub yats: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct Watch {
+    // Time between lookups, e.g. 60s or 5m
+    #[arg(long, short, default_value = "60s", value_parser = humantime::parse_duration)]
+    pub interval: Duration,
+    // File with one yat per line, `-` reads them from the standard input. Replaces `--yat`
+    #[arg(long)]
+    pub yats_file: Option<PathBuf>,
+    // Print changes as NDJSON
+    #[arg(long)]
+    pub json: bool,
+    // Shell command run on each change. The change is passed as JSON in YAT_CHANGE
+    #[arg(long)]
+    pub exec: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct Manifest {
+    // TOML file with the desired payment addresses of each yat
+    #[arg(long, short = 'f')]
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct Restore {
+    // Backup file
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct Login {
+    // Account email
+    #[arg(long, short)]
+    pub email: String,
+    // Account password. Prompted for, if not given
+    #[arg(long, short)]
+    pub password: Option<String>,
+}
+
+fn validate_currency(s: &str) -> Result<Currency, String> {
+    Currency::try_from(s).map_err(|err| format!("{}", err))
+}
+
+fn validate_payment_currency(s: &str) -> Result<Currency, String> {
+    let currency = validate_currency(s)?;
+    if currency.is_token() {
+        return Err("Token addresses require --token and --network".to_owned());
+    }
+    Ok(currency)
+}
+
+fn validate_tag(s: &str) -> Result<Tag, String> {
+    Tag::try_from(s).map_err(|err| format!("{}", err))
+}
+
+fn validate_public_key(s: &str) -> Result<PublicKey, String> {
+    PublicKey::try_from(s).map_err(|err| format!("{}", err))
+}
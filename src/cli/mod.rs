@@ -0,0 +1,562 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    time::Duration,
+};
+
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use yat::{
+    api::{
+        backup::Backup,
+        cache::describe_age,
+        client::ApiClient,
+        edit_request::{EditRequest, NewRecord},
+        error::ClientError,
+        manifest::{read_manifest, PlanSummary, YatPlan},
+        payment_address::{
+            FriendlyPaymentAddress, PaymentAddresses, SignatureStatus, UnknownPaymentAddress,
+            YatLookup,
+        },
+        watch::{diff_addresses, AddressChange},
+    },
+    base::yat::Yat,
+    settings::{loader::Loader, settings_reader::EnvSettingsReader, Settings},
+};
+
+use args::{Cli, Commands, LookupAddress, Manifest, RecordsAction, Watch};
+
+// Lookups running at once in watch mode
+const WATCH_CONCURRENCY: usize = 8;
+
+mod args;
+
+/// Parses the arguments and executes the command. Exits on invalid arguments or settings.
+pub async fn run() {
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let mut settings = Loader::new(EnvSettingsReader).load().unwrap_or_else(|err| {
+        log::error!("{}", err);
+        std::process::exit(1);
+    });
+
+    let args = Cli::parse();
+    settings.cache.offline = args.offline;
+    if let Commands::Watch(_) = args.command {
+        // Every poll revalidates with the API
+        settings.cache.ttl = Duration::ZERO;
+    }
+    let yat = args.yat.as_ref().map(|yat| {
+        Yat::new(&settings, yat).unwrap_or_else(|err| {
+            let mut cmd = Cli::command();
+            cmd.error(ErrorKind::InvalidValue, err).exit();
+        })
+    });
+
+    if let Err(err) = execute(settings, args, yat).await {
+        log::error!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Commands working with a yat require `--yat`
+fn require_yat(yat: Option<Yat>) -> Yat {
+    yat.unwrap_or_else(|| {
+        let mut cmd = Cli::command();
+        cmd.error(
+            ErrorKind::MissingRequiredArgument,
+            "The argument '--yat <YAT>' is required for this command",
+        )
+        .exit();
+    })
+}
+
+fn should_display_address(address: &FriendlyPaymentAddress, lookup: &LookupAddress) -> bool {
+    if lookup.default_only && !address.default {
+        return false;
+    }
+    if let Some(currency) = &lookup.currency {
+        return address.currency == *currency;
+    }
+    true
+}
+
+/// Addresses to display. Unknown categories fail the lookup with `--strict`, addresses without
+/// a verified signature fail it with `--require-signed`
+fn filter_addresses(
+    yat: &str,
+    mut addresses: PaymentAddresses,
+    lookup: &LookupAddress,
+) -> Result<(Vec<FriendlyPaymentAddress>, Vec<UnknownPaymentAddress>), ClientError> {
+    if let Some(key) = &lookup.public_key {
+        addresses.verify_signatures(yat, key);
+    }
+    let (known, unknown) = if lookup.strict {
+        (addresses.strict()?, vec![])
+    } else {
+        (addresses.known, addresses.unknown)
+    };
+    let mut known: Vec<FriendlyPaymentAddress> = known
+        .into_iter()
+        .filter(|a| should_display_address(a, lookup))
+        .collect();
+    if lookup.currency.is_some() {
+        // The default address leads, if the currency has several
+        known.sort_by_key(|a| !a.default);
+    }
+    // Unknown categories can not match a currency filter
+    let unknown: Vec<UnknownPaymentAddress> = unknown
+        .into_iter()
+        .filter(|a| lookup.currency.is_none() && (!lookup.default_only || a.0.default))
+        .collect();
+    if lookup.require_signed {
+        let unsigned: Vec<&FriendlyPaymentAddress> = known
+            .iter()
+            .filter(|a| a.signature_status != SignatureStatus::Verified)
+            .collect();
+        for address in &unsigned {
+            log::warn!(
+                "{} address {}: signature is {:?}",
+                address.currency.get_name(),
+                address.address,
+                address.signature_status
+            );
+        }
+        // Signatures of unknown categories can not be verified
+        if !unsigned.is_empty() || !unknown.is_empty() {
+            return Err(ClientError::UnsignedAddresses(
+                unsigned.len() + unknown.len(),
+            ));
+        }
+    }
+    Ok((known, unknown))
+}
+
+fn print_addresses(
+    known: Vec<FriendlyPaymentAddress>,
+    unknown: Vec<UnknownPaymentAddress>,
+    cached_age: Option<Duration>,
+) {
+    if let Some(age) = cached_age {
+        println!("Cached {} ago", describe_age(age));
+    }
+    if known.is_empty() && unknown.is_empty() {
+        println!("No payment addresses found!");
+        return;
+    }
+    let (tokens, coins): (Vec<_>, Vec<_>) = known.into_iter().partition(|a| a.is_token());
+    for address in coins {
+        println!("{}", address);
+    }
+    if !tokens.is_empty() {
+        println!("Tokens:");
+    }
+    for address in tokens {
+        println!("{}", address);
+    }
+    for address in unknown {
+        println!("{}", address);
+    }
+}
+
+/// Polls the yats forever, reporting changes against the last successful lookup of each
+async fn watch_addresses(client: &ApiClient<'_>, yats: Vec<String>, watch: &Watch) {
+    log::info!(
+        "Watching {} yat(s) every {}",
+        yats.len(),
+        humantime::format_duration(watch.interval)
+    );
+    let mut snapshots: HashMap<String, Vec<FriendlyPaymentAddress>> = HashMap::new();
+    loop {
+        for YatLookup { yat, result } in client.fetch_many(yats.clone(), WATCH_CONCURRENCY).await {
+            let addresses = match result {
+                Ok(addresses) => addresses.known,
+                Err(err) => {
+                    log::error!("{}: {}", yat, err);
+                    continue;
+                }
+            };
+            if let Some(previous) = snapshots.get(&yat) {
+                for change in diff_addresses(previous, &addresses) {
+                    report_change(&yat, &change, watch).await;
+                }
+            }
+            snapshots.insert(yat, addresses);
+        }
+        tokio::time::sleep(watch.interval).await;
+    }
+}
+
+async fn report_change(yat: &str, change: &AddressChange, watch: &Watch) {
+    let json = change.to_json(yat);
+    if watch.json {
+        println!("{}", json);
+    } else {
+        println!("{}: {}", yat, change);
+    }
+    if let Some(command) = &watch.exec {
+        let status = shell(command)
+            .env("YAT", yat)
+            .env("YAT_CHANGE", json.to_string())
+            .status()
+            .await;
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => log::warn!("`{}` failed with {}", command, status),
+            Err(err) => log::error!("`{}` could not be run: {}", command, err),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut shell = tokio::process::Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut shell = tokio::process::Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+/// Yats listed one per line. Empty lines and `#` comments are skipped
+fn read_yats(path: &Path) -> io::Result<Vec<String>> {
+    let content = if path == Path::new("-") {
+        io::read_to_string(io::stdin())?
+    } else {
+        fs::read_to_string(path)?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
+
+/// How mutating commands treat their edit requests
+#[derive(Clone, Copy)]
+struct EditMode {
+    dry_run: bool, // Only show the request
+    yes: bool,     // Do not ask for confirmation
+}
+
+/// Shows the edit request and sends it, unless it is a dry run. A declined confirmation fails.
+/// Returns `true`, if the request was sent.
+async fn apply_edit(
+    client: &ApiClient<'_>,
+    yat: &Yat,
+    edit_request: &EditRequest,
+    mode: EditMode,
+) -> Result<bool, ClientError> {
+    if edit_request.is_empty() {
+        println!("Nothing to change!");
+        return Ok(false);
+    }
+    println!("{}", client.preview_edit(yat, edit_request));
+    if mode.dry_run {
+        println!("Dry run, nothing was sent");
+        return Ok(false);
+    }
+    if !mode.yes {
+        confirm(&format!("Apply the changes to {}?", yat.as_ref()))?;
+    }
+    client.apply_edit(yat, edit_request).await?;
+    Ok(true)
+}
+
+/// Plans every yat of the manifest. Nothing is planned, if any of the yats is invalid
+async fn plan_manifest(
+    client: &ApiClient<'_>,
+    settings: &Settings,
+    manifest: &Manifest,
+) -> Result<Vec<(Yat, YatPlan)>, ClientError> {
+    let manifest = read_manifest(&manifest.file)?;
+    let yats = manifest
+        .yats
+        .iter()
+        .map(|desired| Yat::new(settings, &desired.emoji_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut plans = vec![];
+    for (yat, desired) in yats.into_iter().zip(&manifest.yats) {
+        let plan = client.plan_yat(&yat, &desired.addresses).await?;
+        plans.push((yat, plan));
+    }
+    for (_, plan) in &plans {
+        println!("{}", plan);
+    }
+    println!("{}", PlanSummary::new(plans.iter().map(|(_, plan)| plan)));
+    Ok(plans)
+}
+
+/// Sends one edit request per changed yat, once the plan is confirmed.
+/// Returns the number of changed yats.
+async fn apply_plans(
+    client: &ApiClient<'_>,
+    plans: &[(Yat, YatPlan)],
+    mode: EditMode,
+) -> Result<usize, ClientError> {
+    let pending: Vec<(&Yat, EditRequest)> = plans
+        .iter()
+        .filter(|(_, plan)| !plan.is_empty())
+        .map(|(yat, plan)| (yat, plan.edit_request()))
+        .collect();
+    if pending.is_empty() {
+        println!("Nothing to change!");
+        return Ok(0);
+    }
+    if mode.dry_run {
+        for (yat, edit_request) in &pending {
+            println!("{}", client.preview_edit(yat, edit_request));
+        }
+        println!("Dry run, nothing was sent");
+        return Ok(0);
+    }
+    if !mode.yes {
+        confirm(&format!("Apply the changes to {} yat(s)?", pending.len()))?;
+    }
+    for (yat, edit_request) in &pending {
+        client.apply_edit(yat, edit_request).await?;
+    }
+    Ok(pending.len())
+}
+
+/// Asks on the terminal. Without one, e.g. in scripts, the changes need `--yes`
+fn confirm(question: &str) -> Result<(), ClientError> {
+    if !io::stdin().is_terminal() {
+        return Err(ClientError::ConfirmationRequired);
+    }
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Err(ClientError::ConfirmationRequired);
+    }
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err(ClientError::Cancelled),
+    }
+}
+
+async fn execute(settings: Settings, args: Cli, yat: Option<Yat>) -> Result<(), ClientError> {
+    let client = ApiClient::new(&settings)?;
+    let edit_mode = EditMode {
+        dry_run: args.dry_run,
+        yes: args.yes,
+    };
+    match args.command {
+        Commands::LookupAddress(lookup) if lookup.yats_file.is_some() || lookup.all_owned => {
+            if yat.is_some() {
+                let mut cmd = Cli::command();
+                cmd.error(
+                    ErrorKind::ArgumentConflict,
+                    "The argument '--yat <YAT>' cannot be used with '--yats-file' or '--all-owned'",
+                )
+                .exit();
+            }
+            let yats = match &lookup.yats_file {
+                Some(path) => read_yats(path)?,
+                None => client
+                    .list_owned_yats()
+                    .await?
+                    .into_iter()
+                    .map(|owned| owned.emoji_id)
+                    .collect(),
+            };
+            let lookups = client.fetch_many(yats, lookup.concurrency.into()).await;
+            let mut failed = 0;
+            for YatLookup { yat, result } in lookups {
+                let cached_age = result.as_ref().ok().and_then(|a| a.cached_age);
+                match result.and_then(|addresses| filter_addresses(&yat, addresses, &lookup)) {
+                    Ok((known, unknown)) => {
+                        println!("{}:", yat);
+                        print_addresses(known, unknown, cached_age);
+                    }
+                    Err(err) => {
+                        log::error!("{}: {}", yat, err);
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(ClientError::LookupFailed(failed));
+            }
+        }
+        Commands::LookupAddress(lookup) => {
+            let yat = require_yat(yat);
+            let addresses = client.fetch_payment_addresses_lenient(&yat).await?;
+            let cached_age = addresses.cached_age;
+            let (known, unknown) = filter_addresses(yat.as_ref(), addresses, &lookup)?;
+            print_addresses(known, unknown, cached_age);
+        }
+        Commands::CreateAddress(create) => {
+            let yat = require_yat(yat);
+            let addresses = create.into_addresses()?;
+            let count = addresses.len();
+            let edit_request = client
+                .plan_create_payment_addresses(&yat, addresses)
+                .await?;
+            if apply_edit(&client, &yat, &edit_request, edit_mode).await? {
+                println!("{} address(es) successfully created!", count);
+            }
+        }
+        Commands::DeleteAddress(delete) => {
+            let yat = require_yat(yat);
+            let edit_request = client
+                .plan_delete_payment_address(&yat, delete.into())
+                .await?;
+            if apply_edit(&client, &yat, &edit_request, edit_mode).await? {
+                println!("Address successfully deleted!");
+            }
+        }
+        Commands::UpdateAddress(update) => {
+            let yat = require_yat(yat);
+            let edit_request = client
+                .plan_replace_payment_address(&yat, update.into())
+                .await?;
+            if apply_edit(&client, &yat, &edit_request, edit_mode).await? {
+                println!("Address successfully updated!");
+            }
+        }
+        Commands::SetDefault(set_default) => {
+            let yat = require_yat(yat);
+            let edit_request = client
+                .plan_set_default_address(&yat, set_default.into())
+                .await?;
+            if apply_edit(&client, &yat, &edit_request, edit_mode).await? {
+                println!("Default address successfully set!");
+            }
+        }
+        Commands::Records(records) => {
+            let yat = require_yat(yat);
+            match records.action {
+                RecordsAction::List { tag } => {
+                    let records: Vec<_> = client
+                        .fetch_records(&yat)
+                        .await?
+                        .into_iter()
+                        .filter(|r| tag.is_none() || r.tag().ok() == tag)
+                        .collect();
+                    if records.is_empty() {
+                        println!("No records found!");
+                    }
+                    for record in records {
+                        println!("{}", record);
+                    }
+                }
+                RecordsAction::Add { tag, data, replace } => {
+                    let record = NewRecord::new(tag, &data)?;
+                    let edit_request = match replace {
+                        true => client.plan_set_record(&yat, record).await?,
+                        false => vec![record].into(),
+                    };
+                    if apply_edit(&client, &yat, &edit_request, edit_mode).await? {
+                        println!("Record successfully added!");
+                    }
+                }
+                RecordsAction::Remove { tag, data } => {
+                    let edit_request = client
+                        .plan_remove_records(&yat, &tag, data.as_deref())
+                        .await?;
+                    if apply_edit(&client, &yat, &edit_request, edit_mode).await? {
+                        println!("Record successfully removed!");
+                    }
+                }
+            }
+        }
+        Commands::Search(search) => {
+            let mut failed = 0;
+            for candidate in search.yats {
+                // Invalid candidates are never sent to the API
+                let result = match Yat::partial(&candidate) {
+                    Ok(yat) => client.search(&yat).await,
+                    Err(err) => Err(err.into()),
+                };
+                match result {
+                    Ok(search) => println!("{}", search),
+                    Err(err) => {
+                        log::error!("{}: {}", candidate, err);
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(ClientError::SearchFailed(failed));
+            }
+        }
+        Commands::Watch(watch) => {
+            let yats = match (&watch.yats_file, yat) {
+                (Some(_), Some(_)) => {
+                    let mut cmd = Cli::command();
+                    cmd.error(
+                        ErrorKind::ArgumentConflict,
+                        "The argument '--yats-file <YATS_FILE>' cannot be used with '--yat <YAT>'",
+                    )
+                    .exit();
+                }
+                (Some(path), None) => read_yats(path)?,
+                (None, yat) => vec![require_yat(yat).as_ref().to_owned()],
+            };
+            watch_addresses(&client, yats, &watch).await;
+        }
+        Commands::Plan(manifest) => {
+            plan_manifest(&client, &settings, &manifest).await?;
+        }
+        Commands::Apply(manifest) => {
+            let plans = plan_manifest(&client, &settings, &manifest).await?;
+            let applied = apply_plans(&client, &plans, edit_mode).await?;
+            if applied > 0 {
+                println!("{} yat(s) successfully updated!", applied);
+            }
+        }
+        Commands::Export => {
+            let yat = require_yat(yat);
+            let backup = client.export(&yat).await?;
+            println!("{}", backup.to_json());
+        }
+        Commands::Restore(restore) => {
+            let backup = Backup::read(&restore.file)?;
+            if let Some(yat) = yat.filter(|yat| yat.as_ref() != backup.yat) {
+                let mut cmd = Cli::command();
+                cmd.error(
+                    ErrorKind::ArgumentConflict,
+                    format!("The backup is of {}, not of {}", backup.yat, yat.as_ref()),
+                )
+                .exit();
+            }
+            if backup.api_url != settings.api_url {
+                log::warn!("The backup was exported from {}", backup.api_url);
+            }
+            let plan = client.plan_restore(&backup).await?;
+            println!("{}", plan);
+            let yat = Yat::new(&settings, &backup.yat)?;
+            if apply_edit(&client, &yat, &plan.edit_request(), edit_mode).await? {
+                println!("Backup successfully restored!");
+            }
+        }
+        Commands::ListOwned => {
+            let yats = client.list_owned_yats().await?;
+            if yats.is_empty() {
+                println!("No yats found!");
+            }
+            for yat in yats {
+                println!("{}", yat);
+            }
+        }
+        Commands::Login(login) => {
+            let password = match login.password {
+                Some(password) => password,
+                None => rpassword::prompt_password("Password: ")?,
+            };
+            client.login(login.email, password).await?;
+            println!("Successfully logged in!");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! Yat payment addresses.
+//!
+//! `base` validates yats and currencies, `api` talks to the Yat API and `settings` loads the
+//! configuration from the environment. `mock_server` (feature `mock-server`) is an in-memory
+//! stand-in for the Yat API.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod api;
+pub mod base;
+#[cfg(any(test, feature = "mock-server"))]
+pub mod mock_server;
+pub mod settings;
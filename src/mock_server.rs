@@ -0,0 +1,306 @@
+//! In-memory stand-in for the Yat API, so that whole flows (create → lookup → delete) can be
+//! tested without the real service.
+//!
+//! Yats and their records live in memory. The server implements `GET /emoji_id/{yat}`,
+//! `GET /emoji_id/{yat}/payment` and `PATCH /emoji_id/{yat}`, requires the `x-api-key` header
+//! and can be told to fail the next requests. When run as a binary, the `/_mock` endpoints
+//! add yats and failures:
+//!
+//! * `POST /_mock/emoji_id/{yat}` adds an empty yat
+//! * `POST /_mock/failures` queues a failure, e.g. `{"kind": "rate_limited", "retry_after": 1}`
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use hyper::{
+    header::{CONTENT_TYPE, RETRY_AFTER},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::oneshot;
+
+use crate::api::{
+    edit_request::EditRequest,
+    payment_address::{PaymentAddress, PaymentAddressResponse},
+    record::Record,
+};
+
+static API_KEY_HEADER: &str = "x-api-key";
+static EMOJI_ID_PATH: &str = "/emoji_id/";
+static MOCK_EMOJI_ID_PATH: &str = "/_mock/emoji_id/";
+static MOCK_FAILURES_PATH: &str = "/_mock/failures";
+
+/// A failure returned instead of the next response
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Failure {
+    NotFound,
+    RateLimited {
+        #[serde(default)]
+        retry_after: Option<u64>, // Seconds
+    },
+    ServerError,
+    MalformedJson,
+}
+
+#[derive(Default)]
+struct State {
+    yats: HashMap<String, Vec<Record>>,
+    failures: VecDeque<Failure>,
+    last_hash: u64,
+}
+
+/// Mock server running on a background task. It stops, when dropped
+pub struct MockYatServer {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl MockYatServer {
+    /// Starts the server on a random local port
+    pub async fn start(api_key: &str) -> Result<Self, hyper::Error> {
+        Self::bind(([127, 0, 0, 1], 0).into(), api_key).await
+    }
+
+    pub async fn bind(address: SocketAddr, api_key: &str) -> Result<Self, hyper::Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let api_key = Arc::new(api_key.to_owned());
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            let api_key = api_key.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    handle(state.clone(), api_key.clone(), request)
+                }))
+            }
+        });
+        let server = Server::try_bind(&address)?.serve(make_service);
+        let address = server.local_addr();
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            let _ = stopped.await;
+        }));
+        log::debug!("Mock server listening on {}", address);
+
+        Ok(Self {
+            address,
+            state,
+            _shutdown: shutdown,
+        })
+    }
+
+    /// Value for `API_URL`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Adds a yat without records. Existing records are kept
+    pub fn add_yat(&self, yat: &str) {
+        self.state().yats.entry(yat.to_owned()).or_default();
+    }
+
+    /// Current records of a yat
+    pub fn records(&self, yat: &str) -> Option<Vec<Record>> {
+        self.state().yats.get(yat).cloned()
+    }
+
+    /// The next request gets the failure instead of its response. Failures are used in order
+    pub fn fail_next(&self, failure: Failure) {
+        self.state().failures.push_back(failure);
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Mock server state is poisoned")
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    api_key: Arc<String>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    let headers = request.headers().clone();
+    let body = hyper::body::to_bytes(request.into_body())
+        .await
+        .unwrap_or_default();
+    let mut state = state.lock().expect("Mock server state is poisoned");
+
+    // Control endpoints are never failed and need no API key
+    if method == Method::POST && path == MOCK_FAILURES_PATH {
+        return Ok(match serde_json::from_slice::<Failure>(&body) {
+            Ok(failure) => {
+                state.failures.push_back(failure);
+                empty(StatusCode::NO_CONTENT)
+            }
+            Err(err) => error(
+                StatusCode::BAD_REQUEST,
+                "ValidationFailed",
+                &err.to_string(),
+            ),
+        });
+    }
+    if let (&Method::POST, Some(yat)) = (&method, path.strip_prefix(MOCK_EMOJI_ID_PATH)) {
+        state.yats.entry(decode(yat)).or_default();
+        return Ok(empty(StatusCode::NO_CONTENT));
+    }
+
+    if let Some(failure) = state.failures.pop_front() {
+        return Ok(failure_response(failure));
+    }
+    let authorized = headers
+        .get(API_KEY_HEADER)
+        .is_some_and(|key| key.as_bytes() == api_key.as_bytes());
+    if !authorized {
+        return Ok(error(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            "Missing or invalid API key",
+        ));
+    }
+
+    let (yat, payment) = match path.strip_prefix(EMOJI_ID_PATH) {
+        Some(rest) => match rest.strip_suffix("/payment") {
+            Some(yat) => (decode(yat), true),
+            None => (decode(rest), false),
+        },
+        None => return Ok(empty(StatusCode::NOT_FOUND)),
+    };
+    let state = &mut *state;
+    let records = match state.yats.get_mut(&yat) {
+        Some(records) => records,
+        None => return Ok(empty(StatusCode::NOT_FOUND)),
+    };
+
+    Ok(match (method, payment) {
+        (Method::GET, true) => ok_json(&payment_response(records)),
+        (Method::GET, false) => ok_json(&json!({ "result": records, "status": true })),
+        (Method::PATCH, false) => match serde_json::from_slice::<EditRequest>(&body) {
+            Ok(edit_request) => edit(records, &mut state.last_hash, edit_request),
+            Err(err) => error(
+                StatusCode::BAD_REQUEST,
+                "ValidationFailed",
+                &err.to_string(),
+            ),
+        },
+        _ => empty(StatusCode::METHOD_NOT_ALLOWED),
+    })
+}
+
+/// Removes and inserts records as a whole. Nothing changes, if any hash is unknown
+fn edit(
+    records: &mut Vec<Record>,
+    last_hash: &mut u64,
+    edit_request: EditRequest,
+) -> Response<Body> {
+    if let Some(hash) = edit_request
+        .delete
+        .iter()
+        .find(|hash| !records.iter().any(|r| r.hash == **hash))
+    {
+        let reason = format!("Record `{}` was not found", hash);
+        return error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "ValidationFailed",
+            &reason,
+        );
+    }
+    records.retain(|r| !edit_request.delete.contains(&r.hash));
+    for insert in edit_request.insert {
+        *last_hash += 1;
+        records.push(Record {
+            tag: insert.tag,
+            data: insert.data,
+            hash: format!("{:016x}", last_hash),
+        });
+    }
+    ok_json(&json!({ "status": true }))
+}
+
+/// Payment addresses of the records with a known currency, keyed by record hash
+fn payment_response(records: &[Record]) -> PaymentAddressResponse {
+    let addresses: BTreeMap<String, PaymentAddress> = records
+        .iter()
+        .filter(|r| r.currency().is_some())
+        .map(|record| {
+            let data = record.payment_data();
+            let address = PaymentAddress {
+                address: data.address,
+                category: record.tag.clone(),
+                default: data.default,
+                description: data.description,
+                long_name: data.token.as_ref().map(|t| t.name.clone()),
+                settlement_network: data.token.as_ref().map(|t| t.network.clone()),
+                short_name: data.token.map(|t| t.symbol),
+                signature: data.signature,
+            };
+            (record.hash.clone(), address)
+        })
+        .collect();
+    PaymentAddressResponse {
+        error: None,
+        result: Some(addresses),
+        status: true,
+    }
+}
+
+fn failure_response(failure: Failure) -> Response<Body> {
+    match failure {
+        Failure::NotFound => empty(StatusCode::NOT_FOUND),
+        Failure::RateLimited { retry_after } => {
+            let mut response = error(StatusCode::TOO_MANY_REQUESTS, "RateLimited", "Slow down");
+            if let Some(seconds) = retry_after {
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, seconds.to_string().parse().expect("Number"));
+            }
+            response
+        }
+        Failure::ServerError => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "InternalError",
+            "Something went wrong",
+        ),
+        Failure::MalformedJson => json_response(StatusCode::OK, "{\"result\": [".to_owned()),
+    }
+}
+
+fn decode(yat: &str) -> String {
+    percent_decode_str(yat).decode_utf8_lossy().into_owned()
+}
+
+fn ok_json(body: &impl Serialize) -> Response<Body> {
+    let body = serde_json::to_string(body).expect("Responses can be serialized");
+    json_response(StatusCode::OK, body)
+}
+
+fn error(status: StatusCode, code: &str, reason: &str) -> Response<Body> {
+    let body = json!({ "error": { "code": code, "reason": reason }, "status": false });
+    json_response(status, body.to_string())
+}
+
+fn json_response(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        "application/json".parse().expect("Valid header"),
+    );
+    response
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
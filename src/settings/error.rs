@@ -8,8 +8,14 @@ pub enum SettingsError {
     VariableMissing(String),
     #[error("Environment variable `{0}` should be a number")]
     NotNumber(String),
+    #[error("Environment variable `{0}` should be `true` or `false`")]
+    NotBoolean(String),
     #[error("Environment variable `{0}` should be greater than 0")]
     NotGreaterThanZero(String),
     #[error("Environment variable `{min}` should not be larger than `{max}`")]
     MustNotBeLarger { min: String, max: String },
+    #[error("Environment variables `{0}` and `{1}` should be set together")]
+    MustBeSetTogether(String, String),
+    #[error("Environment variables `{0}` and `{1}` should not be set together")]
+    MustNotBeSetTogether(String, String),
 }
@@ -1,13 +1,36 @@
-use std::{marker::PhantomData, ops::RangeInclusive, str::FromStr};
+use std::{marker::PhantomData, ops::RangeInclusive, path::PathBuf, str::FromStr, time::Duration};
 
 use num_traits::{Num, One};
 
-use super::{error::SettingsError, settings_reader::SettingsReader, Settings};
+use super::{
+    error::SettingsError, settings_reader::SettingsReader, CacheSettings, ClientIdentity,
+    FixtureMode, HttpSettings, RetryPolicy, Settings,
+};
 
 static API_URL: &str = "API_URL";
 static YAT_API_KEY: &str = "YAT_API_KEY";
+static YAT_SESSION_FILE: &str = "YAT_SESSION_FILE";
 static MIN_YAT_LENGTH: &str = "MIN_YAT_LENGTH";
 static MAX_YAT_LENGTH: &str = "MAX_YAT_LENGTH";
+static YAT_RETRY_MAX_ATTEMPTS: &str = "YAT_RETRY_MAX_ATTEMPTS";
+static YAT_RETRY_BASE_DELAY_MS: &str = "YAT_RETRY_BASE_DELAY_MS";
+static YAT_RETRY_MAX_DELAY_MS: &str = "YAT_RETRY_MAX_DELAY_MS";
+static YAT_RETRY_JITTER: &str = "YAT_RETRY_JITTER";
+static YAT_RETRY_PATCH: &str = "YAT_RETRY_PATCH";
+static YAT_HTTP_CONNECT_TIMEOUT_SECS: &str = "YAT_HTTP_CONNECT_TIMEOUT_SECS";
+static YAT_HTTP_TIMEOUT_SECS: &str = "YAT_HTTP_TIMEOUT_SECS";
+static YAT_HTTP_USER_AGENT: &str = "YAT_HTTP_USER_AGENT";
+static YAT_HTTP_POOL_IDLE_TIMEOUT_SECS: &str = "YAT_HTTP_POOL_IDLE_TIMEOUT_SECS";
+static YAT_HTTP_POOL_MAX_IDLE_PER_HOST: &str = "YAT_HTTP_POOL_MAX_IDLE_PER_HOST";
+static YAT_HTTP_PROXY: &str = "YAT_HTTP_PROXY";
+static YAT_HTTP_NO_PROXY: &str = "YAT_HTTP_NO_PROXY";
+static YAT_HTTP_CA_BUNDLE: &str = "YAT_HTTP_CA_BUNDLE";
+static YAT_HTTP_CLIENT_CERT: &str = "YAT_HTTP_CLIENT_CERT";
+static YAT_HTTP_CLIENT_KEY: &str = "YAT_HTTP_CLIENT_KEY";
+static YAT_HTTP_RECORD: &str = "YAT_HTTP_RECORD";
+static YAT_HTTP_REPLAY: &str = "YAT_HTTP_REPLAY";
+static YAT_CACHE_DIR: &str = "YAT_CACHE_DIR";
+static YAT_CACHE_TTL_SECS: &str = "YAT_CACHE_TTL_SECS";
 
 /// Settings loader.
 /// Complication with 'a lifetime arises from the need to use `mockall` for testing
@@ -28,10 +51,14 @@ impl<'a, T: SettingsReader<'a>> Loader<'a, T> {
     pub fn load(&self) -> Result<Settings, SettingsError> {
         // We do not care, if .env file is missing. The user might have the environment variables
         // defined other way
-        let _ = self.settings_reader.load_dot_env();
+        self.settings_reader.load_dot_env();
 
         let api_url = self.get_env_var(API_URL)?;
-        let yat_api_key = self.get_env_var(YAT_API_KEY)?;
+        let yat_api_key = self.get_optional_env_var(YAT_API_KEY);
+        let session_file = self
+            .get_optional_env_var(YAT_SESSION_FILE)
+            .map(PathBuf::from)
+            .or_else(default_session_file);
         let min_yat_length = self.get_number(MIN_YAT_LENGTH)?;
         let max_yat_length = self.get_number(MAX_YAT_LENGTH)?;
         if min_yat_length > max_yat_length {
@@ -43,7 +70,130 @@ impl<'a, T: SettingsReader<'a>> Loader<'a, T> {
         Ok(Settings {
             api_url,
             yat_api_key,
+            session_file,
             required_yat_length: RangeInclusive::new(min_yat_length, max_yat_length),
+            retry: self.load_retry_policy()?,
+            http: self.load_http_settings()?,
+            cache: self.load_cache_settings()?,
+        })
+    }
+
+    // Cache variables are optional, defaults are used for the missing ones.
+    // A TTL of 0 disables the cache
+    fn load_cache_settings(&self) -> Result<CacheSettings, SettingsError> {
+        let default = CacheSettings::default();
+        let dir = self
+            .get_optional_env_var(YAT_CACHE_DIR)
+            .map(PathBuf::from)
+            .or_else(default_cache_dir);
+        let ttl = match self.settings_reader.var(YAT_CACHE_TTL_SECS) {
+            Ok(val) => val
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .map_err(|_| SettingsError::NotNumber(YAT_CACHE_TTL_SECS.to_owned()))?,
+            Err(_) => default.ttl,
+        };
+        Ok(CacheSettings {
+            dir: dir.filter(|_| !ttl.is_zero()),
+            ttl,
+            offline: default.offline,
+        })
+    }
+
+    // HTTP variables are optional, defaults are used for the missing ones
+    fn load_http_settings(&self) -> Result<HttpSettings, SettingsError> {
+        let default = HttpSettings::default();
+        Ok(HttpSettings {
+            connect_timeout: self
+                .get_optional_number(YAT_HTTP_CONNECT_TIMEOUT_SECS)?
+                .map(Duration::from_secs)
+                .unwrap_or(default.connect_timeout),
+            timeout: self
+                .get_optional_number(YAT_HTTP_TIMEOUT_SECS)?
+                .map(Duration::from_secs)
+                .unwrap_or(default.timeout),
+            user_agent: self
+                .get_optional_env_var(YAT_HTTP_USER_AGENT)
+                .unwrap_or(default.user_agent),
+            pool_idle_timeout: self
+                .get_optional_number(YAT_HTTP_POOL_IDLE_TIMEOUT_SECS)?
+                .map(Duration::from_secs)
+                .unwrap_or(default.pool_idle_timeout),
+            pool_max_idle_per_host: self
+                .get_optional_number(YAT_HTTP_POOL_MAX_IDLE_PER_HOST)?
+                .unwrap_or(default.pool_max_idle_per_host),
+            proxy: self.get_optional_env_var(YAT_HTTP_PROXY),
+            no_proxy: self
+                .get_optional_env_var(YAT_HTTP_NO_PROXY)
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|h| !h.is_empty())
+                        .map(str::to_owned)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ca_bundle: self
+                .get_optional_env_var(YAT_HTTP_CA_BUNDLE)
+                .map(PathBuf::from),
+            client_identity: self.load_client_identity()?,
+            fixtures: self.load_fixture_mode()?,
+        })
+    }
+
+    // Recording and replaying exclude each other
+    fn load_fixture_mode(&self) -> Result<Option<FixtureMode>, SettingsError> {
+        let record = self.get_optional_env_var(YAT_HTTP_RECORD);
+        let replay = self.get_optional_env_var(YAT_HTTP_REPLAY);
+        match (record, replay) {
+            (Some(path), None) => Ok(Some(FixtureMode::Record(PathBuf::from(path)))),
+            (None, Some(path)) => Ok(Some(FixtureMode::Replay(PathBuf::from(path)))),
+            (None, None) => Ok(None),
+            _ => Err(SettingsError::MustNotBeSetTogether(
+                YAT_HTTP_RECORD.to_owned(),
+                YAT_HTTP_REPLAY.to_owned(),
+            )),
+        }
+    }
+
+    // Certificate and key are required together
+    fn load_client_identity(&self) -> Result<Option<ClientIdentity>, SettingsError> {
+        let cert = self.get_optional_env_var(YAT_HTTP_CLIENT_CERT);
+        let key = self.get_optional_env_var(YAT_HTTP_CLIENT_KEY);
+        match (cert, key) {
+            (Some(cert), Some(key)) => Ok(Some(ClientIdentity {
+                cert: PathBuf::from(cert),
+                key: PathBuf::from(key),
+            })),
+            (None, None) => Ok(None),
+            _ => Err(SettingsError::MustBeSetTogether(
+                YAT_HTTP_CLIENT_CERT.to_owned(),
+                YAT_HTTP_CLIENT_KEY.to_owned(),
+            )),
+        }
+    }
+
+    // Retry variables are optional, defaults are used for the missing ones
+    fn load_retry_policy(&self) -> Result<RetryPolicy, SettingsError> {
+        let default = RetryPolicy::default();
+        Ok(RetryPolicy {
+            max_attempts: self
+                .get_optional_number(YAT_RETRY_MAX_ATTEMPTS)?
+                .unwrap_or(default.max_attempts),
+            base_delay: self
+                .get_optional_number(YAT_RETRY_BASE_DELAY_MS)?
+                .map(Duration::from_millis)
+                .unwrap_or(default.base_delay),
+            max_delay: self
+                .get_optional_number(YAT_RETRY_MAX_DELAY_MS)?
+                .map(Duration::from_millis)
+                .unwrap_or(default.max_delay),
+            jitter: self
+                .get_optional_bool(YAT_RETRY_JITTER)?
+                .unwrap_or(default.jitter),
+            retry_patch: self
+                .get_optional_bool(YAT_RETRY_PATCH)?
+                .unwrap_or(default.retry_patch),
         })
     }
 
@@ -53,19 +203,64 @@ impl<'a, T: SettingsReader<'a>> Loader<'a, T> {
             .map_err(|_| SettingsError::VariableMissing(name.to_owned()))
     }
 
+    // Empty values are treated as missing
+    fn get_optional_env_var(&self, name: &'a str) -> Option<String> {
+        self.settings_reader
+            .var(name)
+            .ok()
+            .filter(|v| !v.is_empty())
+    }
+
     fn get_number<N>(&self, name: &'a str) -> Result<N, SettingsError>
     where
         N: FromStr + PartialOrd + Num,
     {
         let val = self.get_env_var(name)?;
-        let n = val
-            .parse::<N>()
-            .map_err(|_| SettingsError::NotNumber(name.to_owned()))?;
-        if n < One::one() {
-            return Err(SettingsError::NotGreaterThanZero(name.to_owned()));
+        parse_number(name, &val)
+    }
+
+    fn get_optional_number<N>(&self, name: &'a str) -> Result<Option<N>, SettingsError>
+    where
+        N: FromStr + PartialOrd + Num,
+    {
+        match self.settings_reader.var(name) {
+            Ok(val) => parse_number(name, &val).map(Some),
+            Err(_) => Ok(None),
         }
-        Ok(n)
     }
+
+    fn get_optional_bool(&self, name: &'a str) -> Result<Option<bool>, SettingsError> {
+        match self.settings_reader.var(name) {
+            Ok(val) => val
+                .parse::<bool>()
+                .map(Some)
+                .map_err(|_| SettingsError::NotBoolean(name.to_owned())),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Default location of the login session: `<config dir>/yat/session.json`
+fn default_session_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("yat").join("session.json"))
+}
+
+/// Default location of cached lookups: `<cache dir>/yat`
+fn default_cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("yat"))
+}
+
+fn parse_number<N>(name: &str, val: &str) -> Result<N, SettingsError>
+where
+    N: FromStr + PartialOrd + Num,
+{
+    let n = val
+        .parse::<N>()
+        .map_err(|_| SettingsError::NotNumber(name.to_owned()))?;
+    if n < One::one() {
+        return Err(SettingsError::NotGreaterThanZero(name.to_owned()));
+    }
+    Ok(n)
 }
 
 #[cfg(test)]
@@ -84,7 +279,7 @@ mod tests {
         }
 
         impl SettingsReader<'static> for SettingsReader {
-            fn load_dot_env(&self) -> dotenv::Result<PathBuf>;
+            fn load_dot_env(&self) -> Option<PathBuf>;
             fn var<K: AsRef<OsStr> + 'static>(&self, key: K) -> Result<String, VarError>;
         }
     }
@@ -96,7 +291,7 @@ mod tests {
         reader
             .expect_load_dot_env()
             .times(1)
-            .returning(|| Ok(PathBuf::new()));
+            .returning(|| Some(PathBuf::new()));
         if let Some(env) = env {
             for (k, v) in env {
                 let key: String = k.clone().into();
@@ -224,9 +419,249 @@ mod tests {
             r,
             Ok(Settings {
                 api_url: "http://url".to_owned(),
-                yat_api_key: "KEY".to_owned(),
+                yat_api_key: Some("KEY".to_owned()),
+                session_file: default_session_file(),
                 required_yat_length: (1..=5),
+                retry: RetryPolicy::default(),
+                http: HttpSettings::default(),
+                cache: CacheSettings {
+                    dir: default_cache_dir(),
+                    ..Default::default()
+                },
+            })
+        )
+    }
+
+    #[test]
+    fn it_loads_session_file_without_api_key() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", ""),
+                ("YAT_SESSION_FILE", "/tmp/session.json"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load().unwrap();
+
+        assert_eq!(r.yat_api_key, None);
+        assert_eq!(r.session_file, Some(PathBuf::from("/tmp/session.json")));
+    }
+
+    #[test]
+    fn it_loads_http_settings_from_environment() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_HTTP_CONNECT_TIMEOUT_SECS", "2"),
+                ("YAT_HTTP_TIMEOUT_SECS", "5"),
+                ("YAT_HTTP_USER_AGENT", "agent"),
+                ("YAT_HTTP_POOL_IDLE_TIMEOUT_SECS", "60"),
+                ("YAT_HTTP_POOL_MAX_IDLE_PER_HOST", "2"),
+                ("YAT_HTTP_PROXY", "socks5://proxy:1080"),
+                ("YAT_HTTP_NO_PROXY", "localhost, .internal,"),
+                ("YAT_HTTP_CA_BUNDLE", "/etc/ca.pem"),
+                ("YAT_HTTP_CLIENT_CERT", "/etc/client.pem"),
+                ("YAT_HTTP_CLIENT_KEY", "/etc/client.key"),
+                ("YAT_HTTP_REPLAY", "/tmp/fixtures.json"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r.map(|s| s.http),
+            Ok(HttpSettings {
+                connect_timeout: Duration::from_secs(2),
+                timeout: Duration::from_secs(5),
+                user_agent: "agent".to_owned(),
+                pool_idle_timeout: Duration::from_secs(60),
+                pool_max_idle_per_host: 2,
+                proxy: Some("socks5://proxy:1080".to_owned()),
+                no_proxy: vec!["localhost".to_owned(), ".internal".to_owned()],
+                ca_bundle: Some(PathBuf::from("/etc/ca.pem")),
+                client_identity: Some(ClientIdentity {
+                    cert: PathBuf::from("/etc/client.pem"),
+                    key: PathBuf::from("/etc/client.key"),
+                }),
+                fixtures: Some(FixtureMode::Replay(PathBuf::from("/tmp/fixtures.json"))),
             })
         )
     }
+
+    #[test]
+    fn it_fails_if_client_key_is_missing() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_HTTP_CLIENT_CERT", "/etc/client.pem"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r,
+            Err(SettingsError::MustBeSetTogether(
+                "YAT_HTTP_CLIENT_CERT".to_owned(),
+                "YAT_HTTP_CLIENT_KEY".to_owned()
+            ))
+        )
+    }
+
+    #[test]
+    fn it_fails_if_recording_and_replaying() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_HTTP_RECORD", "/tmp/a.json"),
+                ("YAT_HTTP_REPLAY", "/tmp/b.json"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r,
+            Err(SettingsError::MustNotBeSetTogether(
+                "YAT_HTTP_RECORD".to_owned(),
+                "YAT_HTTP_REPLAY".to_owned()
+            ))
+        )
+    }
+
+    #[test]
+    fn it_loads_cache_settings_from_environment() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_CACHE_DIR", "/tmp/yat"),
+                ("YAT_CACHE_TTL_SECS", "60"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r.map(|s| s.cache),
+            Ok(CacheSettings {
+                dir: Some(PathBuf::from("/tmp/yat")),
+                ttl: Duration::from_secs(60),
+                offline: false,
+            })
+        )
+    }
+
+    #[test]
+    fn it_disables_cache_with_zero_ttl() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_CACHE_DIR", "/tmp/yat"),
+                ("YAT_CACHE_TTL_SECS", "0"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r.map(|s| s.cache),
+            Ok(CacheSettings {
+                dir: None,
+                ttl: Duration::ZERO,
+                offline: false,
+            })
+        )
+    }
+
+    #[test]
+    fn it_loads_retry_policy_from_environment() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_RETRY_MAX_ATTEMPTS", "5"),
+                ("YAT_RETRY_BASE_DELAY_MS", "100"),
+                ("YAT_RETRY_MAX_DELAY_MS", "1000"),
+                ("YAT_RETRY_JITTER", "false"),
+                ("YAT_RETRY_PATCH", "true"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r.map(|s| s.retry),
+            Ok(RetryPolicy {
+                max_attempts: 5,
+                base_delay: Duration::from_millis(100),
+                max_delay: Duration::from_millis(1000),
+                jitter: false,
+                retry_patch: true,
+            })
+        )
+    }
+
+    #[test]
+    fn it_fails_if_retry_flag_is_not_boolean() {
+        let mut reader = MockSettingsReader::new();
+        setup_settings_reader::<&str>(
+            &mut reader,
+            Some(&[
+                ("API_URL", "http://url"),
+                ("YAT_API_KEY", "KEY"),
+                ("MIN_YAT_LENGTH", "1"),
+                ("MAX_YAT_LENGTH", "5"),
+                ("YAT_RETRY_JITTER", "maybe"),
+            ]),
+        );
+
+        let loader = Loader::new(reader);
+        let r = loader.load();
+
+        assert_eq!(
+            r,
+            Err(SettingsError::NotBoolean("YAT_RETRY_JITTER".to_owned()))
+        )
+    }
 }
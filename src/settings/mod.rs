@@ -1,4 +1,4 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, path::PathBuf, time::Duration};
 
 pub mod error;
 pub mod loader;
@@ -8,8 +8,118 @@ pub mod settings_reader;
 pub struct Settings {
     // Corresponds to API_URL env variable
     pub api_url: String,
-    // Corresponds to YAT_API_KEY env variable
-    pub yat_api_key: String,
+    // Corresponds to YAT_API_KEY env variable. A login session is used, if it is missing
+    pub yat_api_key: Option<String>,
+    // Corresponds to YAT_SESSION_FILE env variable, defaults to <config dir>/yat/session.json
+    pub session_file: Option<PathBuf>,
     // Corresponds to MIN_YAT_LENGTH and MAX_YAT_LENGTH env variables
     pub required_yat_length: RangeInclusive<u32>,
+    pub retry: RetryPolicy,
+    pub http: HttpSettings,
+    pub cache: CacheSettings,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RetryPolicy {
+    // Corresponds to YAT_RETRY_MAX_ATTEMPTS env variable. 1 disables retries
+    pub max_attempts: u32,
+    // Corresponds to YAT_RETRY_BASE_DELAY_MS env variable
+    pub base_delay: Duration,
+    // Corresponds to YAT_RETRY_MAX_DELAY_MS env variable. Caps the backoff.
+    // A longer Retry-After is not waited for, the request fails instead
+    pub max_delay: Duration,
+    // Corresponds to YAT_RETRY_JITTER env variable
+    pub jitter: bool,
+    // Corresponds to YAT_RETRY_PATCH env variable. Only idempotent requests are retried otherwise
+    pub retry_patch: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            retry_patch: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HttpSettings {
+    // Corresponds to YAT_HTTP_CONNECT_TIMEOUT_SECS env variable
+    pub connect_timeout: Duration,
+    // Corresponds to YAT_HTTP_TIMEOUT_SECS env variable. Applies to the whole request
+    pub timeout: Duration,
+    // Corresponds to YAT_HTTP_USER_AGENT env variable
+    pub user_agent: String,
+    // Corresponds to YAT_HTTP_POOL_IDLE_TIMEOUT_SECS env variable
+    pub pool_idle_timeout: Duration,
+    // Corresponds to YAT_HTTP_POOL_MAX_IDLE_PER_HOST env variable
+    pub pool_max_idle_per_host: usize,
+    // Corresponds to YAT_HTTP_PROXY env variable. http://, https:// and socks5:// are supported.
+    // System proxy variables are used, if missing
+    pub proxy: Option<String>,
+    // Corresponds to YAT_HTTP_NO_PROXY env variable, a comma separated list of hosts.
+    // Applies to the system proxy too, if YAT_HTTP_PROXY is missing
+    pub no_proxy: Vec<String>,
+    // Corresponds to YAT_HTTP_CA_BUNDLE env variable. PEM file with extra root certificates
+    pub ca_bundle: Option<PathBuf>,
+    // Corresponds to YAT_HTTP_CLIENT_CERT and YAT_HTTP_CLIENT_KEY env variables (PEM files)
+    pub client_identity: Option<ClientIdentity>,
+    // Corresponds to YAT_HTTP_RECORD or YAT_HTTP_REPLAY env variable
+    pub fixtures: Option<FixtureMode>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClientIdentity {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// Request and response fixtures of the API client
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FixtureMode {
+    Record(PathBuf), // Requests go to the API, exchanges are appended to the file
+    Replay(PathBuf), // Responses come from the file, the API is never called
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(30),
+            user_agent: format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 8,
+            proxy: None,
+            no_proxy: vec![],
+            ca_bundle: None,
+            client_identity: None,
+            fixtures: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CacheSettings {
+    // Corresponds to YAT_CACHE_DIR env variable, defaults to <cache dir>/yat.
+    // Missing, if the cache is disabled or there is no cache dir
+    pub dir: Option<PathBuf>,
+    // Corresponds to YAT_CACHE_TTL_SECS env variable. Older entries are revalidated with the API.
+    // 0 disables the cache
+    pub ttl: Duration,
+    // Set by `--offline`. Only cached lookups are served, the API is never called
+    pub offline: bool,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            ttl: Duration::from_secs(300),
+            offline: false,
+        }
+    }
 }
@@ -1,18 +1,25 @@
-use dotenv::dotenv;
 use std::{env, env::VarError, ffi::OsStr, path::PathBuf};
 
 /// A settings reader with two implementations. One for live app, one for tests to be able to mock.
 /// Lifetime parameter 'a is required, since `mockall` needs all references to be 'static.
 pub trait SettingsReader<'a> {
-    fn load_dot_env(&self) -> dotenv::Result<PathBuf>;
+    /// Loads .env file into the environment. Returns its path, if it was found
+    fn load_dot_env(&self) -> Option<PathBuf>;
     fn var<K: AsRef<OsStr> + 'a>(&self, key: K) -> Result<String, VarError>;
 }
 
+/// Reads the process environment. .env file is loaded only with the `cli` feature
 pub struct EnvSettingsReader;
 
 impl<'a> SettingsReader<'a> for EnvSettingsReader {
-    fn load_dot_env(&self) -> dotenv::Result<PathBuf> {
-        dotenv()
+    #[cfg(feature = "cli")]
+    fn load_dot_env(&self) -> Option<PathBuf> {
+        dotenv::dotenv().ok()
+    }
+
+    #[cfg(not(feature = "cli"))]
+    fn load_dot_env(&self) -> Option<PathBuf> {
+        None
     }
 
     fn var<K: AsRef<OsStr> + 'a>(&self, key: K) -> Result<String, VarError> {